@@ -0,0 +1,154 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use board::WIN_LENGTH;
+
+// each row takes an extra, always clear, bit, so that no run can carry
+// on from the end of one row into the start of the next
+const STRIDE : usize = BOARD_SIZE + 1;
+
+const WORDS : usize = (BOARD_SIZE * STRIDE).div_ceil(64);
+
+// from each cell to the next along rows, columns, up and down diagonals
+const SHIFTS : [usize; 4] = [1, STRIDE, STRIDE - 1, STRIDE + 1];
+
+type Bits = [u64; WORDS];
+
+/**
+ * A copy of a board's stones as one bitset per color, for scanning for
+ * sixes with a few shifts and ANDs rather than cell by cell.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct BitBoard {
+    black : Bits,
+    white : Bits,
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board:&Board) -> BitBoard {
+        let mut bits = BitBoard { black: [0; WORDS], white: [0; WORDS] };
+        for (r, c, piece) in board.stones() {
+            let i = r * STRIDE + c;
+            let set = if piece == Piece::Black { &mut bits.black } else { &mut bits.white };
+            set[i / 64] |= 1 << (i % 64);
+        }
+        bits
+    }
+}
+
+impl BitBoard {
+    /**
+     * Whether piece has WIN_LENGTH or more stones in a row, as for
+     * Board::winner.
+     */
+    pub fn has_six(&self, piece:Piece) -> bool {
+        assert!(piece != Piece::Empty);
+        let bits = if piece == Piece::Black { &self.black } else { &self.white };
+        SHIFTS.iter().any(|&step| {
+            // the cells starting a run of WIN_LENGTH
+            let mut runs = *bits;
+            for k in 1..WIN_LENGTH {
+                runs = and(&runs, &shr(bits, k * step));
+            }
+            runs.iter().any(|&word| word != 0)
+        })
+    }
+
+    // the color with six in a row, if any
+    pub fn winner(&self) -> Option<Piece> {
+        [Piece::Black, Piece::White].iter().cloned().find(|&piece| self.has_six(piece))
+    }
+}
+
+fn and(a:&Bits, b:&Bits) -> Bits {
+    let mut result = [0; WORDS];
+    for i in 0..WORDS {
+        result[i] = a[i] & b[i];
+    }
+    result
+}
+
+// bit i of the result is bit i + n of bits
+fn shr(bits:&Bits, n:usize) -> Bits {
+    let (words, n) = (n / 64, n % 64);
+    let mut result = [0; WORDS];
+    for i in 0..WORDS - words.min(WORDS) {
+        result[i] = bits[i + words] >> n;
+        if n > 0 && i + words + 1 < WORDS {
+            result[i] |= bits[i + words + 1] << (64 - n);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitBoard;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+    use board::DIRECTIONS;
+    use board::WIN_LENGTH;
+
+    #[test]
+    fn test_has_six() {
+        assert_eq!(BitBoard::from(&Board::empty()).winner(), None);
+        // a six along each direction, ending at the edge of the board
+        let last = BOARD_SIZE - WIN_LENGTH;
+        let starts = [(0, last), (last, BOARD_SIZE - 1), (WIN_LENGTH - 1, last), (last, last)];
+        for (&(rstride, cstride), &(row, col)) in DIRECTIONS.iter().zip(starts.iter()) {
+            let mut b = Board::empty();
+            for i in 0..WIN_LENGTH as i32 {
+                b.set((row as i32 + i * rstride) as usize, (col as i32 + i * cstride) as usize, Piece::White);
+            }
+            let bits = BitBoard::from(&b);
+            assert!(bits.has_six(Piece::White));
+            assert!(!bits.has_six(Piece::Black));
+            assert_eq!(bits.winner(), b.winner());
+        }
+
+        // three at the end of a row and three at the start of the next
+        let mut b = Board::empty();
+        for i in 0..3 {
+            b.set(CENTER, BOARD_SIZE - 1 - i, Piece::Black);
+            b.set(CENTER + 1, i, Piece::Black);
+        }
+        assert!(!BitBoard::from(&b).has_six(Piece::Black));
+        for c in 0..WIN_LENGTH - 1 {
+            b.set(0, c, Piece::Black);
+        }
+        assert!(!BitBoard::from(&b).has_six(Piece::Black));
+        b.set(0, WIN_LENGTH - 1, Piece::Black);
+        assert!(BitBoard::from(&b).has_six(Piece::Black));
+    }
+
+    #[test]
+    fn test_has_six_matches_lines() {
+        // xorshift, so that every run checks the same boards
+        let mut seed:u64 = 6;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize
+        };
+        let mut wins = 0;
+        for i in 0..300 {
+            let mut b = Board::empty();
+            for _ in 0..100 + i % 200 {
+                let cell = next() % (BOARD_SIZE * BOARD_SIZE);
+                let piece = if next() % 2 == 0 { Piece::Black } else { Piece::White };
+                b.set(cell / BOARD_SIZE, cell % BOARD_SIZE, piece);
+            }
+            let bits = BitBoard::from(&b);
+            for &piece in [Piece::Black, Piece::White].iter() {
+                let by_lines = b.lines().any(|line| line.longest_run(piece) >= WIN_LENGTH);
+                assert_eq!(bits.has_six(piece), by_lines);
+                wins += by_lines as usize;
+            }
+        }
+        // both outcomes were tried
+        assert!(wins > 0 && wins < 600);
+    }
+}
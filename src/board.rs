@@ -1,65 +1,235 @@
+use std::convert::TryFrom;
 use std::fmt;
+use std::iter;
+use std::mem;
+use std::str::FromStr;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use coord::column_letter;
+use error::Error;
+use zobrist::Zobrist;
 
 pub const BOARD_SIZE : usize = 19;
 
 pub const CENTER : usize = BOARD_SIZE / 2;
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+pub const WIN_LENGTH : usize = 6;
+
+// rows, columns, and both families of diagonals
+pub const LINE_COUNT : usize = line_count(BOARD_SIZE);
+
+/**
+ * The number of lines on a size x size board such as a Board<13>: size
+ * rows and columns, and 2 * size - 1 diagonals each way.
+ */
+pub const fn line_count(size:usize) -> usize {
+    2 * size + 2 * (2 * size - 1)
+}
+
+// a line's index for Board::line, below LINE_COUNT
+pub type LineId = usize;
+
+// (rstride, cstride) of rows, columns, up diagonals and down diagonals
+pub const DIRECTIONS : [(i32, i32); 4] = [(0, 1), (1, 0), (-1, 1), (1, 1)];
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Piece {
     Empty,
     Black,
     White,
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct Line {
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    // the input had found lines rather than the expected number
+    LineCount { expected: usize, found: usize },
+    // the given input line had the wrong number of characters
+    LineWidth { line: usize, width: usize },
+    // an unexpected character at the given line and index
+    BadGlyph { line: usize, index: usize, glyph: char },
+}
+
+// why a single line string, as for Line::try_from, couldn't be parsed
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LineParseError {
+    // a line of zero characters, or more than BOARD_SIZE
+    Width(usize),
+    // an unexpected character at the given index
+    BadGlyph { index: usize, glyph: char },
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PositionError {
+    // stone counts no sequence of turns could produce
+    Unreachable { black: usize, white: usize },
+    // both colors have six in a row
+    TwoWinners,
+    // the winner isn't the player who placed the last stone
+    WrongWinner(Piece),
+}
+
+// a flat board of the given length rather than BOARD_SIZE * BOARD_SIZE
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct LenError(pub usize);
+
+// the row and column of a cell holding different stones on two boards
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ConflictError(pub usize, pub usize);
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PlaceError {
+    // a cell off the board
+    OutOfBounds(usize, usize),
+    // the cell already holds a stone, existing
+    Occupied { row: usize, col: usize, existing: Piece },
+    // Piece::Empty, which isn't a stone, was to be placed at the cell
+    NoStone(usize, usize),
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum BitsetError {
+    // the cell at row, col is set in both masks
+    Overlap(usize, usize),
+    // a bit at or beyond BOARD_SIZE * BOARD_SIZE is set
+    OutOfRange(usize),
+}
+
+// where a cell lies on the board
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CellKind {
+    Corner,
+    Edge,
+    Interior,
+}
+
+// up to N cells copied from a Board<N>, a standard board unless N is given
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Line<const N: usize = BOARD_SIZE> {
     size : usize,
-    cells : [Piece; BOARD_SIZE]
+    cells : [Piece; N]
 }
 
-#[derive(Copy, Clone)]
-struct Row {
-    cells : [Piece; BOARD_SIZE]
+// how many cells of a line hold each color, from Line::summary
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct LineSummary {
+    pub black : usize,
+    pub white : usize,
+    pub empty : usize,
 }
 
-#[derive(Copy, Clone)]
-pub struct Board {
-    rows : [Row; BOARD_SIZE]
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+struct Row<const N: usize> {
+    cells : [Piece; N]
 }
 
-impl Row {
+/**
+ * An N x N board, BOARD_SIZE x BOARD_SIZE unless N is given as in
+ * Board::<13>::empty(). Cells, lines and their iterators work for any N
+ * from 1 to BOARD_SIZE; the analysis and search built on them is for the
+ * standard board. Boards are equal when every cell is; the other fields
+ * follow from those.
+ */
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Board<const N: usize = BOARD_SIZE> {
+    rows : [Row<N>; N],
+    // the number of empty cells, kept up to date by try_set
+    empty : usize,
+    // the zobrist hash, also kept up to date by try_set
+    hash : u64,
+}
+
+// the board the game is played on, for spelling out where N isn't inferred
+pub type StandardBoard = Board<BOARD_SIZE>;
+
+impl Piece {
+    // the other color; Empty has no opponent
+    pub fn opponent(self) -> Piece {
+        match self {
+            Piece::Black => Piece::White,
+            Piece::White => Piece::Black,
+            Piece::Empty => Piece::Empty,
+        }
+    }
+}
+
+impl<const N: usize> Row<N> {
     fn get(&self, col:usize) -> Piece {
         self.cells[col]
     }
     
-    fn set(&mut self, col:usize, val:Piece) {
-        self.cells[col] = val
+    fn set(&mut self, col:usize, val:Piece) -> Piece {
+        mem::replace(&mut self.cells[col], val)
     }
     
-    fn empty() -> Row {
-        Row { cells: [Piece::Empty; BOARD_SIZE] }
+    fn empty() -> Row<N> {
+        Row { cells: [Piece::Empty; N] }
     }
 }
 
-impl Board {
+impl<const N: usize> Board<N> {
     // coordinates are relative to the lower left corner
     pub fn get(&self, row:usize, col:usize) -> Piece {
-        if row >= BOARD_SIZE || col >= BOARD_SIZE {
-            panic!("({}, {}) out of range", row, col)
+        self.try_get(row, col).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get(&self, row:usize, col:usize) -> Result<Piece, Error> {
+        if row >= N || col >= N {
+            return Err(Error::OutOfBounds(row, col))
         }
-        self.rows[row].get(col)
+        Ok(self.rows[row].get(col))
     }
     
-    pub fn set(&mut self, row:usize, col:usize, val:Piece) {
-        self.rows[row].set(col, val)
+    // returns the piece previously at row, col
+    pub fn set(&mut self, row:usize, col:usize, val:Piece) -> Piece {
+        self.try_set(row, col, val).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set(&mut self, row:usize, col:usize, val:Piece) -> Result<Piece, Error> {
+        if row >= N || col >= N {
+            return Err(Error::OutOfBounds(row, col))
+        }
+        let old = self.rows[row].set(col, val);
+        let zobrist = Zobrist::standard();
+        self.hash ^= zobrist.key(row, col, old) ^ zobrist.key(row, col, val);
+        if old == Piece::Empty {
+            self.empty -= 1;
+        }
+        if val == Piece::Empty {
+            self.empty += 1;
+        }
+        Ok(old)
+    }
+
+    // as for place, returning only whether val was placed
+    pub fn set_if_empty(&mut self, row:usize, col:usize, val:Piece) -> bool {
+        self.place(row, col, val).is_ok()
+    }
+
+    /**
+     * Put the stone val on the empty cell at row, col, or leave the board
+     * alone if the cell is off the board or already holds a stone, or val
+     * is Empty. Unlike set this never overwrites.
+     */
+    pub fn place(&mut self, row:usize, col:usize, val:Piece) -> Result<(), PlaceError> {
+        match self.try_get(row, col) {
+            Err(_) => Err(PlaceError::OutOfBounds(row, col)),
+            Ok(Piece::Empty) if val == Piece::Empty => Err(PlaceError::NoStone(row, col)),
+            Ok(Piece::Empty) => {
+                self.set(row, col, val);
+                Ok(())
+            },
+            Ok(existing) => Err(PlaceError::Occupied { row, col, existing }),
+        }
     }
     
-    fn get_row(&self, row:usize) -> Line {
-        Line::on(self, row, 0, 0, 1, BOARD_SIZE)
+    fn get_row(&self, row:usize) -> LineView<'_, N> {
+        LineView { board: self, row, col: 0, rstride: 0, cstride: 1, size: N }
     }
     
-    fn get_col(&self, col:usize) -> Line {
-        Line::on(self, 0, col, 1, 0, BOARD_SIZE)
+    fn get_col(&self, col:usize) -> LineView<'_, N> {
+        LineView { board: self, row: 0, col, rstride: 1, cstride: 0, size: N }
     }
 
     /**
@@ -68,242 +238,1489 @@ impl Board {
      * One of row or col must be zero. If both are zero, this
      * is the main diagonal.
      */
-    fn get_down_diagonal(&self, row:usize, col:usize) -> Line {
+    fn get_down_diagonal(&self, row:usize, col:usize) -> LineView<'_, N> {
         assert!(row == 0 || col == 0);
-        assert!(row < BOARD_SIZE);
-        assert!(col < BOARD_SIZE);
+        assert!(row < N);
+        assert!(col < N);
 
-        let len = BOARD_SIZE - row - col;
-        Line::on(self, row, col, 1, 1, len)
+        let size = N - row - col;
+        LineView { board: self, row, col, rstride: 1, cstride: 1, size }
     }
     
     /**
      * Get a diagonal line starting at row, col, and moving
      * towards the top of the board and to the right.
-     * Either col must be zero, or row must be BOARD_SIZE-1. 
-     * If col is zero and row is BOARD_SIZE, this is the 
+     * Either col must be zero, or row must be N-1. 
+     * If col is zero and row is N-1, this is the 
      * anti-diagonal.
      */
-    fn get_up_diagonal(&self, row:usize, col:usize) -> Line {
-        assert!(row == BOARD_SIZE-1 || col == 0);
-        assert!(row < BOARD_SIZE);
-        assert!(col < BOARD_SIZE);
+    fn get_up_diagonal(&self, row:usize, col:usize) -> LineView<'_, N> {
+        assert!(row == N-1 || col == 0);
+        assert!(row < N);
+        assert!(col < N);
 
-        let len = row - col + 1;
-        Line::on(self, row, col, -1, 1, len)
+        let size = row - col + 1;
+        LineView { board: self, row, col, rstride: -1, cstride: 1, size }
     }
 
-    pub fn empty() -> Board {
-        Board { rows : [Row::empty(); BOARD_SIZE] }
+    /**
+     * An empty N x N board. Boards have at most BOARD_SIZE cells on a
+     * side, the size the Zobrist table has keys for.
+     */
+    pub fn empty() -> Board<N> {
+        assert!(N >= 1 && N <= BOARD_SIZE, "boards are 1 to {} cells wide, not {}", BOARD_SIZE, N);
+        Board {
+            rows : [Row::empty(); N],
+            empty : N * N,
+            hash : Zobrist::standard().empty_hash(N),
+        }
     }
-}
 
-impl Line {
-    fn empty(size:usize) -> Line {
-        assert!(size <= BOARD_SIZE);
-        assert!(size >= 1);
-        Line { size: size, cells: [Piece::Empty; BOARD_SIZE] }
+    /**
+     * The position's hash under the crate's standard Zobrist table. The
+     * board size is part of the hash. It is updated as stones are set
+     * rather than computed afresh.
+     */
+    pub fn zobrist(&self) -> u64 {
+        self.hash
     }
-    
-    fn on(board:&Board, row:usize, col:usize, rstride:i32, cstride:i32, size:usize) -> Line {
-        let mut line = Line::empty(size);
-        for i in 0..size {
-            let val = board.get(
-                (row as i32 + (i as i32 * rstride)) as usize, 
-                (col as i32 + (i as i32 * cstride)) as usize
-            );
-            line.set(i, val);
+
+    /**
+     * Whether the hash kept up to date by set matches one computed from
+     * the stones, as it always should. A check for tests and debugging.
+     */
+    pub fn verify_hash(&self) -> bool {
+        self.hash == Zobrist::standard().hash(self)
+    }
+
+    /**
+     * Get a line by its position in the order LineIterator visits them:
+     * rows, then columns, then up diagonals, then down diagonals.
+     * Index must be less than line_count(N), LINE_COUNT on a standard
+     * board.
+     */
+    pub fn line(&self, index:usize) -> Line<N> {
+        self.try_line(index).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_line(&self, index:usize) -> Result<Line<N>, Error> {
+        let max = N - 1;
+        let diagonals = 2 * N - 1;
+        if index >= line_count(N) {
+            return Err(Error::LineIndex(index))
+        }
+
+        if index < N {
+            return Ok(self.get_row(index).to_line())
         }
-        line
+        let index = index - N;
+        if index < N {
+            return Ok(self.get_col(index).to_line())
+        }
+        let index = index - N;
+        if index < diagonals {
+            // (0,0)->(max,0)->(max,max)
+            return Ok(if index <= max {
+                self.get_up_diagonal(index, 0)
+            } else {
+                self.get_up_diagonal(max, index - max)
+            }.to_line())
+        }
+        // (0,max)->(0,0)->(max,0)
+        let index = index - diagonals;
+        Ok(if index <= max {
+            self.get_down_diagonal(0, max - index)
+        } else {
+            self.get_down_diagonal(index - max, 0)
+        }.to_line())
     }
-    
-    // primarily for testing, at least for now
-    fn of(s:&str) -> Line {
-        let mut line = Line::empty(s.len());
-        for (i, c) in s.chars().enumerate() {
-            match c {
-                '-' => line.set(i, Piece::Empty),
-                'O' => line.set(i, Piece::White),
-                'X' => line.set(i, Piece::Black),
-                _ => panic!("Line strings must consist only of -, O and X (not {})", c)
+
+    // the rows and then the columns, as for line(0) to line(2 * N - 1)
+    pub fn orthogonals(&self) -> impl Iterator<Item = Line<N>> + '_ {
+        LineIterator::on(self).take(2 * N)
+    }
+
+    // the up diagonals and then the down diagonals, including the corners
+    pub fn diagonals(&self) -> impl Iterator<Item = Line<N>> + '_ {
+        LineIterator::on(self).skip(2 * N)
+    }
+
+    // every line, as line(0) to line(line_count(N) - 1)
+    pub fn lines(&self) -> impl Iterator<Item = Line<N>> + '_ {
+        LineIterator::on(self)
+    }
+
+    /**
+     * Every line in the same order as line(0) to line(line_count(N) - 1),
+     * as views which read the board's cells as they are asked for rather
+     * than copying them.
+     */
+    pub fn line_views(&self) -> impl Iterator<Item = LineView<'_, N>> {
+        let mut lines = LineIterator::on(self);
+        iter::from_fn(move || lines.next_view())
+    }
+
+    /**
+     * Every line as for lines(), each with the board coordinates of its
+     * cells, so that a run found in the line can be located on the board.
+     */
+    pub fn located_lines(&self) -> impl Iterator<Item = (Vec<(usize, usize)>, Line<N>)> + '_ {
+        self.line_views().map(|view| ((0..view.size()).map(|i| view.cell(i)).collect(), view.to_line()))
+    }
+
+    /**
+     * The number of cells holding the given piece.
+     */
+    pub fn count(&self, piece:Piece) -> usize {
+        self.rows.iter()
+            .map(|row| row.cells.iter().filter(|&&cell| cell == piece).count())
+            .sum()
+    }
+
+    // the same as count(Piece::Empty), without scanning the board
+    pub fn empty_count(&self) -> usize {
+        self.empty
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.empty == 0
+    }
+
+    /**
+     * A board with the stones of both boards, or the first cell in
+     * row-major order where they hold different stones.
+     */
+    pub fn overlay(&self, other:&Board<N>) -> Result<Board<N>, ConflictError> {
+        let mut board = *self;
+        for (r, c, piece) in other.stones() {
+            match board.set(r, c, piece) {
+                Piece::Empty => (),
+                p if p == piece => (),
+                _ => return Err(ConflictError(r, c)),
             }
         }
-        line
+        Ok(board)
     }
-    
-    fn set(&mut self, index:usize, val:Piece) {
-        assert!(index < self.size);
-        self.cells[index] = val
+
+    // the number of cells whose pieces differ between the boards
+    pub fn hamming_distance(&self, other:&Board<N>) -> usize {
+        self.rows.iter().zip(other.rows.iter())
+            .map(|(a, b)| a.cells.iter().zip(b.cells.iter()).filter(|&(x, y)| x != y).count())
+            .sum()
     }
-    
-    fn get(&self, index:usize) -> Piece {
-        self.cells[index]
+
+    /**
+     * Each cell whose piece differs between the boards, with its piece on
+     * other, in row-major order. Setting each of them on this board makes
+     * it other.
+     */
+    pub fn diff(&self, other:&Board<N>) -> Vec<(usize, usize, Piece)> {
+        (0..N)
+            .flat_map(|r| (0..N).map(move |c| (r, c)))
+            .filter(|&(r, c)| self.get(r, c) != other.get(r, c))
+            .map(|(r, c)| (r, c, other.get(r, c)))
+            .collect()
     }
-    
-    pub fn size(&self) -> usize {
-        self.size
+
+    // the fraction of cells holding a stone, from 0.0 to 1.0
+    pub fn fill_ratio(&self) -> f64 {
+        let cells = N * N;
+        (cells - self.empty_count()) as f64 / cells as f64
     }
-}
 
-impl fmt::Display for Line {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        try!(fmt.write_str("["));
-        for i in 0..self.size() {
-	        match self.get(i) {
-		        Piece::Empty => try!(fmt.write_str("-")),
-	            Piece::White => try!(fmt.write_str("O")),
-                Piece::Black => try!(fmt.write_str("X")),
-            }
+    // the mean (row, col) of piece's stones, or None if it has none
+    pub fn centroid(&self, piece:Piece) -> Option<(f64, f64)> {
+        let (mut n, mut rows, mut cols) = (0, 0, 0);
+        for (r, c, _) in self.stones().filter(|&(_, _, p)| p == piece) {
+            n += 1;
+            rows += r;
+            cols += c;
+        }
+        if n == 0 {
+            return None
         }
-        try!(fmt.write_str("]"));
-	    Ok(())
+        Some((rows as f64 / n as f64, cols as f64 / n as f64))
     }
-}
 
-#[derive(Debug)]
-enum State {
-    Row(usize),
-    Col(usize),
-    DownDiag(usize, usize), 
-    UpDiag(usize, usize),
-    Finished,
-}
+    /**
+     * Every stone on the board with its color, in row-major order.
+     */
+    pub fn stones(&self) -> impl Iterator<Item = (usize, usize, Piece)> + '_ {
+        (0..N).flat_map(move |r| {
+            (0..N)
+                .map(move |c| (r, c, self.get(r, c)))
+                .filter(|&(_, _, piece)| piece != Piece::Empty)
+        })
+    }
 
-struct LineIterator<'a> {
-    board:&'a Board,
-    state:State,
-}
+    // every cell without a stone, in row-major order
+    pub fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..N)
+            .flat_map(|r| (0..N).map(move |c| (r, c)))
+            .filter(move |&(r, c)| self.get(r, c) == Piece::Empty)
+    }
 
-impl<'a> LineIterator<'a> {
-    fn on(board:&Board) -> LineIterator {
-        LineIterator { board: board, state: State::Row(0) }
+    /**
+     * Every empty cell within Chebyshev distance radius of a stone, each
+     * once, in row-major order: the candidates for most moves. There are
+     * none on an empty board, where only the center is worth playing.
+     */
+    pub fn cells_near_stones(&self, radius:usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.empty_cells().filter(move |&(r, c)| {
+            let rows = r.saturating_sub(radius)..=(r + radius).min(N - 1);
+            rows.flat_map(|r| {
+                (c.saturating_sub(radius)..=(c + radius).min(N - 1)).map(move |c| (r, c))
+            }).any(|(r, c)| self.get(r, c) != Piece::Empty)
+        })
     }
-}
 
-impl<'a> Iterator for LineIterator<'a> {
-    type Item = Line;
-    
-    fn next(&mut self) -> Option<Line> {
-        let result = match self.state {
-            State::Row(r) => Some(self.board.get_row(r)),
-            State::Col(c) => Some(self.board.get_col(c)),
-            State::UpDiag(r, c) => Some(self.board.get_up_diagonal(r, c)),
-            State::DownDiag(r, c) => Some(self.board.get_down_diagonal(r, c)),
-            State::Finished => None
-        };
-        self.state = self.state.next();
-        return result
+    // the number of black and of white stones, e.g. to tell whose turn it is
+    pub fn stone_count(&self) -> (usize, usize) {
+        (self.count(Piece::Black), self.count(Piece::White))
     }
-}
 
-impl State {
-    fn next(&self) -> State {
-        const MAX:usize = BOARD_SIZE - 1;
-        match self {
-            // 0->MAX
-            &State::Row(MAX) => State::Col(0), 
-            &State::Row(i)   => State::Row(i+1),
-
-            // 0->MAX
-            &State::Col(MAX) => State::UpDiag(0, 0),
-            &State::Col(i)   => State::Col(i+1),
-
-            // (0,0)->(MAX,0)->(MAX,MAX)
-            &State::UpDiag(MAX, 0)   => State::UpDiag(MAX, 1),
-            &State::UpDiag(MAX, MAX) => State::DownDiag(0, MAX),
-            &State::UpDiag(r, 0)     => State::UpDiag(r+1, 0),
-            &State::UpDiag(MAX, c)   => State::UpDiag(MAX, c+1),
-
-            // (0,MAX)->(0,0)->(MAX,0)
-            &State::DownDiag(0, 0)   => State::DownDiag(1, 0),
-            &State::DownDiag(MAX, 0) => State::Finished,
-            &State::DownDiag(0, c)   => State::DownDiag(0, c-1),
-            &State::DownDiag(r, 0)   => State::DownDiag(r+1, 0),
+    /**
+     * Every cell within Chebyshev distance radius of the center, with its
+     * piece, in row-major order. The region is clipped to the board.
+     */
+    pub fn center_region(&self, radius:usize) -> impl Iterator<Item = (usize, usize, Piece)> + '_ {
+        let center = N / 2;
+        let low = center.saturating_sub(radius);
+        let high = (center + radius).min(N - 1);
+        (low..=high).flat_map(move |r| (low..=high).map(move |c| (r, c, self.get(r, c))))
+    }
 
-            // terminal
-            &State::Finished => State::Finished,
-            
-            _ => panic!("Illegal state {:?}", self)
+    /**
+     * Whether the stone counts are consistent with some sequence of
+     * Connect6 turns, possibly stopping between the two stones of a turn.
+     * Nothing but the counts is checked.
+     */
+    pub fn is_reachable(&self) -> bool {
+        let total = self.count(Piece::Black) + self.count(Piece::White);
+        let black = (0..total).filter(|&i| stone_color(i) == Piece::Black).count();
+        self.count(Piece::Black) == black
+    }
+
+    /**
+     * Check that the position could have arisen in a game: the stone
+     * counts are reachable, and if anyone has won it is one color, the
+     * one which placed the last stone.
+     */
+    pub fn validate(&self) -> Result<(), PositionError> {
+        let black = self.count(Piece::Black);
+        let white = self.count(Piece::White);
+        if !self.is_reachable() {
+            return Err(PositionError::Unreachable { black, white })
+        }
+        let won = |piece| self.min_stones_to_win(piece) == Some(0);
+        match (won(Piece::Black), won(Piece::White)) {
+            (true, true) => Err(PositionError::TwoWinners),
+            (false, false) => Ok(()),
+            (black_won, _) => {
+                let winner = if black_won { Piece::Black } else { Piece::White };
+                // the stones are numbered from 0, so this is the last one's color
+                if winner == stone_color(black + white - 1) {
+                    Ok(())
+                } else {
+                    Err(PositionError::WrongWinner(winner))
+                }
+            },
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::Board;
-    use super::Piece;
-    use super::State;
-    use super::Line;
-    use board::CENTER;
-    use board::BOARD_SIZE;
+    /**
+     * A board with the given number of stones on distinct random cells,
+     * colored as if they had been played in order with Black opening
+     * and each player then placing two stones per turn.
+     */
+    #[cfg(feature = "rand")]
+    pub fn random(rng:&mut impl Rng, stones:usize) -> Board<N> {
+        assert!(stones <= N * N);
+        let mut board = Board::empty();
+        let mut empty:Vec<(usize, usize)> = (0..N)
+            .flat_map(|r| (0..N).map(move |c| (r, c)))
+            .collect();
+        for i in 0..stones {
+            let (r, c) = empty.swap_remove(rng.gen_range(0..empty.len()));
+            board.set(r, c, stone_color(i));
+        }
+        board
+    }
 
-    #[test]
-    fn test_get() {
-        let b = Board::empty();
-        assert_eq!(b.get(0, 0), Piece::Empty);        
-        for r in 0..BOARD_SIZE {
-            for c in 0..BOARD_SIZE {
-                assert_eq!(b.get(r, c), Piece::Empty);
-            }
+    /**
+     * Deduce whose turn it is from the stone counts alone, assuming the
+     * position is between turns. Black opens with a single stone, and
+     * each turn after that adds two stones, so after Black's turns Black
+     * has exactly one more stone than White, and after White's turns
+     * White has exactly one more than Black (or the board is empty).
+     * Any other counts cannot arise between turns, and give None.
+     */
+    pub fn infer_to_move(&self) -> Option<Piece> {
+        let black = self.count(Piece::Black);
+        let white = self.count(Piece::White);
+        if black == white + 1 {
+            Some(Piece::White)
+        } else if white == black + 1 || (black == 0 && white == 0) {
+            Some(Piece::Black)
+        } else {
+            None
         }
     }
-    
-    #[test]
-    fn test_set() {
-        let mut b = Board::empty();
-        b.set(CENTER,   CENTER,   Piece::Black);
-        b.set(CENTER+1, CENTER+1, Piece::White);
-        b.set(CENTER,   CENTER-1, Piece::White);
-        let b = b;
-        
-        // check the nine center pieces
-        // x==1
-        assert_eq!(b.get(CENTER+1, CENTER-1), Piece::Empty);
-        assert_eq!(b.get(CENTER+1, CENTER  ), Piece::Empty);
-        assert_eq!(b.get(CENTER+1, CENTER+1), Piece::White);
-        // x==0
-        assert_eq!(b.get(CENTER  , CENTER-1), Piece::White);
-        assert_eq!(b.get(CENTER  , CENTER  ), Piece::Black);
-        assert_eq!(b.get(CENTER  , CENTER+1), Piece::Empty);
-        // x==-1
-        assert_eq!(b.get(CENTER-1, CENTER-1), Piece::Empty);
-        assert_eq!(b.get(CENTER-1, CENTER  ), Piece::Empty);
-        assert_eq!(b.get(CENTER-1, CENTER+1), Piece::Empty);
-        // check the rest of the board
-        for r in 0..BOARD_SIZE {
-            for c in 0..BOARD_SIZE {
-                if (r > CENTER+1 || r < CENTER-1) && (c > CENTER+1 || c < CENTER-1) {
-                    assert_eq!(b.get(r, c), Piece::Empty);
-                }        
-            }
+
+    /**
+     * The number of the turn the stones on the board have reached: 0 on
+     * an empty board, 1 after Black's opening stone, 2 once White has
+     * placed one or both of its first stones, and so on.
+     */
+    pub fn move_number(&self) -> usize {
+        let stones = N * N - self.empty_count();
+        if stones == 0 { 0 } else { stones / 2 + 1 }
+    }
+
+    /**
+     * The segment of cells from a to b inclusive, if the two cells share
+     * a row, column or diagonal.
+     */
+    pub fn line_between(&self, a:(usize, usize), b:(usize, usize)) -> Option<Line<N>> {
+        let dr = b.0 as i32 - a.0 as i32;
+        let dc = b.1 as i32 - a.1 as i32;
+        if dr != 0 && dc != 0 && dr.abs() != dc.abs() {
+            return None
         }
+        let len = dr.abs().max(dc.abs());
+        Some(Line::on(self, a.0, a.1, dr.signum(), dc.signum(), len as usize + 1))
     }
-    
-    #[test]
-    fn test_get_down_diagonal() {
-        let b = Board::empty();
-        
-        assert_eq!(b.get_down_diagonal(0, 0).size(), BOARD_SIZE);
-        assert_eq!(b.get_down_diagonal(1, 0).size(), BOARD_SIZE-1);
-        assert_eq!(b.get_down_diagonal(0, 1).size(), BOARD_SIZE-1);
-        assert_eq!(b.get_down_diagonal(BOARD_SIZE-1, 0).size(), 1);
+
+    /**
+     * The full row, column, up diagonal and down diagonal through a cell,
+     * in that order.
+     */
+    pub fn lines_through(&self, row:usize, col:usize) -> [Line<N>; 4] {
+        let mut lines = [Line::empty(1); 4];
+        for (line, &(rstride, cstride)) in lines.iter_mut().zip(DIRECTIONS.iter()) {
+            let back = steps_to_edge(N, row, col, -rstride, -cstride);
+            let forward = steps_to_edge(N, row, col, rstride, cstride);
+            let start_row = (row as i32 - back as i32 * rstride) as usize;
+            let start_col = (col as i32 - back as i32 * cstride) as usize;
+            *line = Line::on(self, start_row, start_col, rstride, cstride, back + forward + 1);
+        }
+        lines
     }
-    
-    #[test]
-    fn test_get_up_diagonal() {
-        let b = Board::empty();
-        
-        assert_eq!(b.get_up_diagonal(0, 0).size(), 1);
-        assert_eq!(b.get_up_diagonal(1, 0).size(), 2);
-        assert_eq!(b.get_up_diagonal(BOARD_SIZE-1, 0).size(), BOARD_SIZE);
-        assert_eq!(b.get_up_diagonal(BOARD_SIZE-1, BOARD_SIZE-1).size(), 1);
+
+    /**
+     * The rows, columns, up diagonals and down diagonals of the board
+     * clipped to the rectangle with the given corners, inclusive. As rows
+     * count up from the bottom, top_left is the corner with the higher
+     * row, but the corners may be given either way round. Lines which
+     * miss the rectangle are skipped.
+     */
+    pub fn lines_in_region(&self, top_left:(usize, usize), bottom_right:(usize, usize))
+            -> impl Iterator<Item = Line<N>> + '_ {
+        assert!(top_left.0 < N && top_left.1 < N);
+        assert!(bottom_right.0 < N && bottom_right.1 < N);
+        let (min_row, max_row) = (top_left.0.min(bottom_right.0), top_left.0.max(bottom_right.0));
+        let (min_col, max_col) = (top_left.1.min(bottom_right.1), top_left.1.max(bottom_right.1));
+        let inside = move |r:i32, c:i32| {
+            r >= min_row as i32 && r <= max_row as i32 && c >= min_col as i32 && c <= max_col as i32
+        };
+        DIRECTIONS.iter().flat_map(move |&(rstride, cstride)| {
+            (min_row..=max_row).flat_map(move |row| {
+                (min_col..=max_col).filter_map(move |col| {
+                    let (r, c) = (row as i32, col as i32);
+                    // each clipped line starts where the region is entered
+                    if inside(r - rstride, c - cstride) {
+                        return None
+                    }
+                    let mut len = 1;
+                    while inside(r + len * rstride, c + len * cstride) {
+                        len += 1;
+                    }
+                    Some(Line::on(self, row, col, rstride, cstride, len as usize))
+                })
+            })
+        })
     }
-    
-    #[test]
-    fn test_state_transition() {
-        let mut s = State::Row(0);
-        let mut rows = 0;
-        let mut cols = 0;
+
+    /**
+     * The number of empty cells in the rectangle with the given corners,
+     * inclusive, which may be given either way round as for
+     * lines_in_region: how many more stones the region can take.
+     */
+    pub fn region_capacity(&self, top_left:(usize, usize), bottom_right:(usize, usize)) -> usize {
+        assert!(top_left.0 < N && top_left.1 < N);
+        assert!(bottom_right.0 < N && bottom_right.1 < N);
+        let (min_row, max_row) = (top_left.0.min(bottom_right.0), top_left.0.max(bottom_right.0));
+        let (min_col, max_col) = (top_left.1.min(bottom_right.1), top_left.1.max(bottom_right.1));
+        (min_row..=max_row)
+            .map(|r| (min_col..=max_col).filter(|&c| self.get(r, c) == Piece::Empty).count())
+            .sum()
+    }
+
+    /**
+     * The fewest additional stones of the given color needed to complete
+     * a win, considering only windows which contain no opposing stones.
+     * Returns None if every window has been blocked.
+     */
+    pub fn min_stones_to_win(&self, piece:Piece) -> Option<usize> {
+        assert!(piece != Piece::Empty);
+        self.six_windows().filter_map(|window| {
+            let mut needed = 0;
+            for &(r, c) in window.iter() {
+                match self.get(r, c) {
+                    Piece::Empty => needed += 1,
+                    p if p == piece => (),
+                    _ => return None,
+                }
+            }
+            Some(needed)
+        }).min()
+    }
+
+    /**
+     * The color which has WIN_LENGTH or more stones in a row anywhere on
+     * the board, if any.
+     */
+    pub fn winner(&self) -> Option<Piece> {
+        self.six_windows().filter_map(|window| {
+            let (r, c) = window[0];
+            let piece = self.get(r, c);
+            if piece != Piece::Empty && window.iter().all(|&(r, c)| self.get(r, c) == piece) {
+                Some(piece)
+            } else {
+                None
+            }
+        }).next()
+    }
+
+    /**
+     * Whether piece has a run of exactly WIN_LENGTH stones in some line,
+     * with no stone of piece just beyond either end: the win under rules
+     * where an overline doesn't count.
+     */
+    pub fn has_exact_six(&self, piece:Piece) -> bool {
+        assert!(piece != Piece::Empty);
+        (0..line_count(N)).any(|i| {
+            let line = self.line(i);
+            line.as_slice().split(|&p| p != piece).any(|run| run.len() == WIN_LENGTH)
+        })
+    }
+
+    /**
+     * The number of windows without any of the opponent's stones, which
+     * piece could still fill to win.
+     */
+    pub fn live_windows(&self, piece:Piece) -> usize {
+        assert!(piece != Piece::Empty);
+        let opponent = piece.opponent();
+        self.six_windows()
+            .filter(|window| window.iter().all(|&(r, c)| self.get(r, c) != opponent))
+            .count()
+    }
+
+    /**
+     * The empty cells, in row-major order, which neither color can ever
+     * make part of a six: every window through them holds stones of both
+     * colors.
+     */
+    pub fn dead_cells(&self) -> Vec<(usize, usize)> {
+        let mut live = [[false; N]; N];
+        for window in self.six_windows() {
+            let black = window.iter().any(|&(r, c)| self.get(r, c) == Piece::Black);
+            let white = window.iter().any(|&(r, c)| self.get(r, c) == Piece::White);
+            if !(black && white) {
+                for &(r, c) in window.iter() {
+                    live[r][c] = true;
+                }
+            }
+        }
+        (0..N)
+            .flat_map(|r| (0..N).map(move |c| (r, c)))
+            .filter(|&(r, c)| !live[r][c] && self.get(r, c) == Piece::Empty)
+            .collect()
+    }
+
+    /**
+     * How long a run piece could still make in the stretches of each line
+     * free of the opponent's stones: WIN_LENGTH wherever such a stretch
+     * with one of piece's stones is long enough to win in, and otherwise
+     * the longest run already in a stretch too short to win in. 0 if
+     * piece has no stones.
+     */
+    pub fn max_potential_run(&self, piece:Piece) -> usize {
+        assert!(piece != Piece::Empty);
+        let opponent = piece.opponent();
+        let mut best = 0;
+        for line in self.line_views() {
+            let (mut stretch, mut run, mut longest) = (0, 0, 0);
+            for i in 0..=line.size() {
+                // the end of the line closes a stretch like an opposing stone
+                let cell = if i < line.size() { line.get(i) } else { opponent };
+                if cell == opponent {
+                    if longest > 0 {
+                        best = best.max(if stretch >= WIN_LENGTH { WIN_LENGTH } else { longest });
+                    }
+                    stretch = 0;
+                    run = 0;
+                    longest = 0;
+                    continue
+                }
+                stretch += 1;
+                run = if cell == piece { run + 1 } else { 0 };
+                longest = longest.max(run);
+            }
+        }
+        best
+    }
+
+    /**
+     * A quick score for sorting candidate cells for a stone of piece,
+     * from the windows through row, col free of the opponent's stones:
+     * those the stone would complete count most, then those it would
+     * bring to four or five stones, then those it would bring to three.
+     * An occupied cell scores 0.
+     */
+    pub fn threat_rank(&self, row:usize, col:usize, piece:Piece) -> u32 {
+        assert!(piece != Piece::Empty);
+        if self.get(row, col) != Piece::Empty {
+            return 0
+        }
+        let (mut sixes, mut fours, mut threes) = (0, 0, 0);
+        for cells in windows_through_on(N, row, col) {
+            match self.stones_after(&cells, row, col, piece) {
+                Some(WIN_LENGTH) => sixes += 1,
+                Some(4) | Some(5) => fours += 1,
+                Some(3) => threes += 1,
+                _ => (),
+            }
+        }
+        // at most 24 windows pass through a cell, so the tiers can't overlap
+        sixes * 10_000 + fours * 100 + threes
+    }
+
+    /**
+     * The window through row, col which a stone of piece there would
+     * leave holding the most of piece's stones, among those free of the
+     * opponent's, or None if row, col is occupied or every such window is
+     * blocked. Of equally full windows the first in DIRECTIONS order,
+     * then from the lowest starting cell, is chosen.
+     */
+    pub fn best_window_for(&self, row:usize, col:usize, piece:Piece) -> Option<[(usize, usize); WIN_LENGTH]> {
+        assert!(piece != Piece::Empty);
+        if self.get(row, col) != Piece::Empty {
+            return None
+        }
+        let mut best:Option<(usize, [(usize, usize); WIN_LENGTH])> = None;
+        for cells in windows_through_on(N, row, col) {
+            if let Some(own) = self.stones_after(&cells, row, col, piece) {
+                if best.is_none_or(|(most, _)| own > most) {
+                    best = Some((own, cells));
+                }
+            }
+        }
+        best.map(|(_, cells)| cells)
+    }
+
+    /**
+     * Piece's stones, in row-major order, which every window through them
+     * holds an opponent's stone, so that they can never be part of a six.
+     */
+    pub fn idle_stones(&self, piece:Piece) -> Vec<(usize, usize)> {
+        assert!(piece != Piece::Empty);
+        let mut idle = Vec::new();
+        for row in 0..N {
+            for col in 0..N {
+                if self.get(row, col) == piece && windows_through_on(N, row, col).iter()
+                    .all(|cells| self.stones_after(cells, row, col, piece).is_none()) {
+                    idle.push((row, col));
+                }
+            }
+        }
+        idle
+    }
+
+    // how many of piece's stones the window would hold with one added at
+    // row, col, or None if it holds any of the opponent's
+    fn stones_after(&self, cells:&[(usize, usize)], row:usize, col:usize, piece:Piece) -> Option<usize> {
+        let mut own = 1;
+        for &(r, c) in cells.iter().filter(|&&cell| cell != (row, col)) {
+            match self.get(r, c) {
+                Piece::Empty => (),
+                p if p == piece => own += 1,
+                _ => return None,
+            }
+        }
+        Some(own)
+    }
+
+    /**
+     * Every window completely filled by piece. A run longer than
+     * WIN_LENGTH produces one window for each overlapping position.
+     */
+    pub fn winning_windows(&self, piece:Piece) -> impl Iterator<Item = [(usize, usize); WIN_LENGTH]> + '_ {
+        self.six_windows()
+            .filter(move |window| window.iter().all(|&(r, c)| self.get(r, c) == piece))
+    }
+
+    /**
+     * Every window completely filled by either color, with that color, in
+     * the order of six_windows. Empty on a board nobody has won.
+     */
+    pub fn all_wins(&self) -> Vec<([(usize, usize); WIN_LENGTH], Piece)> {
+        self.six_windows().filter_map(|window| {
+            let (r, c) = window[0];
+            let piece = self.get(r, c);
+            if piece != Piece::Empty && window.iter().all(|&(r, c)| self.get(r, c) == piece) {
+                Some((window, piece))
+            } else {
+                None
+            }
+        }).collect()
+    }
+
+    /**
+     * Check for a win through the stone just played at row, col. Only
+     * the four lines through the cell are examined, so this is much
+     * cheaper than winner() when checking after each move.
+     */
+    pub fn wins_at(&self, row:usize, col:usize) -> Option<Piece> {
+        let piece = self.get(row, col);
+        if piece == Piece::Empty {
+            return None
+        }
+        for &(rstride, cstride) in DIRECTIONS.iter() {
+            let run = 1
+                + self.count_in_direction(row, col, rstride, cstride, piece)
+                + self.count_in_direction(row, col, -rstride, -cstride, piece);
+            if run >= WIN_LENGTH {
+                return Some(piece)
+            }
+        }
+        None
+    }
+
+    #[deprecated(note = "renamed to wins_at")]
+    pub fn wins_after(&self, row:usize, col:usize) -> Option<Piece> {
+        self.wins_at(row, col)
+    }
+
+    /**
+     * The number of consecutive cells of piece after (but not including)
+     * row, col, stepping by rstride, cstride until the run is broken or
+     * leaves the board. Adding the count in the opposite direction gives
+     * the run through a stone just placed, without scanning whole lines.
+     */
+    pub fn count_in_direction(&self, row:usize, col:usize, rstride:i32, cstride:i32, piece:Piece) -> usize {
+        let mut count = 0;
+        let mut r = row as i32 + rstride;
+        let mut c = col as i32 + cstride;
+        while r >= 0 && r < N as i32 && c >= 0 && c < N as i32
+                && self.get(r as usize, c as usize) == piece {
+            count += 1;
+            r += rstride;
+            c += cstride;
+        }
+        count
+    }
+
+    /**
+     * The coordinates of every run of WIN_LENGTH cells on the board,
+     * in all four directions.
+     */
+    pub fn six_windows(&self) -> impl Iterator<Item = [(usize, usize); WIN_LENGTH]> {
+        DIRECTIONS.iter().flat_map(|&(rstride, cstride)| {
+            (0..N).flat_map(move |row| {
+                (0..N).filter_map(move |col| window(N, row, col, rstride, cstride))
+            })
+        })
+    }
+
+    /**
+     * A board with each stone placed in turn on an empty board, failing at
+     * the first which is off the board, on a cell already taken or Empty.
+     * No turn order is enforced, so any position can be written this way.
+     */
+    pub fn from_moves(moves:&[(Piece, usize, usize)]) -> Result<Board<N>, PlaceError> {
+        let mut board = Board::empty();
+        for &(piece, row, col) in moves {
+            board.place(row, col, piece)?;
+        }
+        Ok(board)
+    }
+
+    // every cell in row-major order, from the lower left corner
+    pub fn to_vec(&self) -> Vec<Piece> {
+        self.rows.iter().flat_map(|row| row.cells.iter().cloned()).collect()
+    }
+
+    // the inverse of to_vec
+    pub fn from_vec(v:&[Piece]) -> Result<Board<N>, LenError> {
+        if v.len() != N * N {
+            return Err(LenError(v.len()))
+        }
+        let mut board = Board::empty();
+        for (row, cells) in board.rows.iter_mut().zip(v.chunks(N)) {
+            row.cells.copy_from_slice(cells);
+        }
+        board.empty = v.iter().filter(|&&cell| cell == Piece::Empty).count();
+        board.hash = Zobrist::standard().hash(&board);
+        Ok(board)
+    }
+
+    /**
+     * A board from a mask of black stones and one of white, in the same
+     * cell order as to_vec: bit i of the masks is bit i % 64 of word
+     * i / 64. Missing words are taken to be zero.
+     */
+    pub fn from_bitsets(black:&[u64], white:&[u64]) -> Result<Board<N>, BitsetError> {
+        let mut board = Board::empty();
+        for &(mask, piece) in [(black, Piece::Black), (white, Piece::White)].iter() {
+            for (w, &word) in mask.iter().enumerate() {
+                for b in (0..64).filter(|b| word & (1 << b) != 0) {
+                    let bit = w * 64 + b;
+                    if bit >= N * N {
+                        return Err(BitsetError::OutOfRange(bit))
+                    }
+                    let (row, col) = (bit / N, bit % N);
+                    if board.set(row, col, piece) != Piece::Empty {
+                        return Err(BitsetError::Overlap(row, col))
+                    }
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /**
+     * Encode the board for a neural network as planes[p][row][col]: plane
+     * 0 is 1.0 where to_move has a stone, plane 1 where the opponent does,
+     * and plane 2 is all 1.0 when Black is to move and all 0.0 otherwise.
+     */
+    pub fn to_planes(&self, to_move:Piece) -> [[[f32; N]; N]; 3] {
+        assert!(to_move != Piece::Empty);
+        let mut planes = [[[0.0; N]; N]; 3];
+        for (r, c, piece) in self.stones() {
+            let plane = if piece == to_move { 0 } else { 1 };
+            planes[plane][r][c] = 1.0;
+        }
+        if to_move == Piece::Black {
+            planes[2] = [[1.0; N]; N];
+        }
+        planes
+    }
+
+    /**
+     * Draw the board as a lattice of intersections, with the highest
+     * row at the top. Empty intersections are drawn as +, joined by -
+     * and | lattice lines, and stones as X and O.
+     */
+    pub fn to_ascii_grid(&self) -> String {
+        self.to_ascii_grid_marked(&[])
+    }
+
+    /**
+     * Draw the board as to_ascii_grid does, but with the stones on the
+     * marked cells (e.g. the last turn's) in lower case, as x and o.
+     */
+    pub fn to_ascii_grid_marked(&self, marked:&[(usize, usize)]) -> String {
+        let mut s = String::new();
+        for r in (0..N).rev() {
+            for c in 0..N {
+                if c > 0 {
+                    s.push('-');
+                }
+                let glyph = match self.get(r, c) {
+                    Piece::Empty => '+',
+                    Piece::Black => 'X',
+                    Piece::White => 'O',
+                };
+                s.push(if marked.contains(&(r, c)) { glyph.to_ascii_lowercase() } else { glyph });
+            }
+            s.push('\n');
+            if r > 0 {
+                for c in 0..N {
+                    s.push_str(if c > 0 { " |" } else { "|" });
+                }
+                s.push('\n');
+            }
+        }
+        s
+    }
+
+    /**
+     * Parse the output of to_ascii_grid or to_ascii_grid_marked. The
+     * lattice lines and marks are ignored, and stones are read from the
+     * intersections.
+     */
+    pub fn from_ascii_grid(s:&str) -> Result<Board<N>, ParseError> {
+        let (height, width) = (2 * N - 1, 2 * N - 1);
+
+        let lines:Vec<&str> = s.lines().collect();
+        if lines.len() != height {
+            return Err(ParseError::LineCount { expected: height, found: lines.len() })
+        }
+        let mut board = Board::empty();
+        for (l, line) in lines.iter().enumerate() {
+            let glyphs:Vec<char> = line.chars().collect();
+            if glyphs.len() != width {
+                return Err(ParseError::LineWidth { line: l, width: glyphs.len() })
+            }
+            if l % 2 == 1 {
+                continue
+            }
+            let row = N - 1 - l / 2;
+            for c in 0..N {
+                let glyph = glyphs[c * 2];
+                let val = match glyph {
+                    '+' => Piece::Empty,
+                    'X' | 'x' => Piece::Black,
+                    'O' | 'o' => Piece::White,
+                    _ => return Err(ParseError::BadGlyph { line: l, index: c * 2, glyph })
+                };
+                board.set(row, c, val);
+            }
+        }
+        Ok(board)
+    }
+}
+
+/**
+ * The color of the i'th stone played in a game, counting from zero:
+ * Black places one stone, then each player places two in turn.
+ */
+pub fn stone_color(i:usize) -> Piece {
+    if i == 0 || ((i - 1) / 2) % 2 == 1 { Piece::Black } else { Piece::White }
+}
+
+// how many steps can be taken from row, col in a direction before leaving the board
+fn steps_to_edge(size:usize, row:usize, col:usize, rstride:i32, cstride:i32) -> usize {
+    let mut steps = 0;
+    let mut r = row as i32 + rstride;
+    let mut c = col as i32 + cstride;
+    while r >= 0 && r < size as i32 && c >= 0 && c < size as i32 {
+        steps += 1;
+        r += rstride;
+        c += cstride;
+    }
+    steps
+}
+
+/**
+ * The number of king moves between two cells.
+ */
+pub fn chebyshev_distance(a:(usize, usize), b:(usize, usize)) -> usize {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+/**
+ * Whether two distinct cells are adjacent horizontally, vertically or
+ * diagonally, i.e. whether a turn placing stones on both is "connected"
+ * rather than "split".
+ */
+pub fn is_connected_pair(a:(usize, usize), b:(usize, usize)) -> bool {
+    chebyshev_distance(a, b) == 1
+}
+
+// corners are on two edges at once
+pub fn cell_kind(row:usize, col:usize) -> CellKind {
+    assert!(row < BOARD_SIZE && col < BOARD_SIZE);
+    let on_edge = |i:usize| i == 0 || i == BOARD_SIZE - 1;
+    match (on_edge(row), on_edge(col)) {
+        (true, true) => CellKind::Corner,
+        (false, false) => CellKind::Interior,
+        _ => CellKind::Edge,
+    }
+}
+
+/**
+ * How many of the four lines through a cell are long enough to hold a
+ * win. The row and column always are; a diagonal near a corner may not be.
+ */
+pub fn lines_count_through(row:usize, col:usize) -> usize {
+    assert!(row < BOARD_SIZE && col < BOARD_SIZE);
+    DIRECTIONS.iter().filter(|&&(rstride, cstride)| {
+        steps_to_edge(BOARD_SIZE, row, col, -rstride, -cstride) + steps_to_edge(BOARD_SIZE, row, col, rstride, cstride) + 1 >= WIN_LENGTH
+    }).count()
+}
+
+/**
+ * Every WIN_LENGTH window on the board containing row, col, in
+ * DIRECTIONS order and then from the one starting furthest back: the
+ * windows a stone there can change. Up to 24 of them, or fewer near the
+ * edges.
+ */
+pub fn windows_through(row:usize, col:usize) -> Vec<[(usize, usize); WIN_LENGTH]> {
+    windows_through_on(BOARD_SIZE, row, col)
+}
+
+// windows_through on a size x size board, such as a Board<13>
+pub fn windows_through_on(size:usize, row:usize, col:usize) -> Vec<[(usize, usize); WIN_LENGTH]> {
+    let mut windows = Vec::new();
+    for &(rstride, cstride) in DIRECTIONS.iter() {
+        for back in (0..WIN_LENGTH as i32).rev() {
+            let (r, c) = (row as i32 - back * rstride, col as i32 - back * cstride);
+            if r < 0 || c < 0 {
+                continue
+            }
+            if let Some(cells) = window(size, r as usize, c as usize, rstride, cstride) {
+                windows.push(cells);
+            }
+        }
+    }
+    windows
+}
+
+// the WIN_LENGTH cells starting at row, col, if they are all on a size x size board
+fn window(size:usize, row:usize, col:usize, rstride:i32, cstride:i32) -> Option<[(usize, usize); WIN_LENGTH]> {
+    let last = WIN_LENGTH as i32 - 1;
+    let end_row = row as i32 + last * rstride;
+    let end_col = col as i32 + last * cstride;
+    if row >= size || col >= size {
+        return None
+    }
+    if end_row < 0 || end_row >= size as i32 || end_col < 0 || end_col >= size as i32 {
+        return None
+    }
+    let mut cells = [(0, 0); WIN_LENGTH];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        *cell = (
+            (row as i32 + i as i32 * rstride) as usize,
+            (col as i32 + i as i32 * cstride) as usize
+        );
+    }
+    Some(cells)
+}
+
+impl<const N: usize> Line<N> {
+    fn empty(size:usize) -> Line<N> {
+        assert!(size <= N);
+        assert!(size >= 1);
+        Line { size, cells: [Piece::Empty; N] }
+    }
+    
+    /**
+     * A copy of size cells of the board, from row, col stepping by
+     * rstride, cstride. Panics if any of them is off the board.
+     */
+    pub fn on(board:&Board<N>, row:usize, col:usize, rstride:i32, cstride:i32, size:usize) -> Line<N> {
+        Self::try_on(board, row, col, rstride, cstride, size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // as for on, reporting a line running off the board as out of bounds at its start
+    pub fn try_on(board:&Board<N>, row:usize, col:usize, rstride:i32, cstride:i32, size:usize) -> Result<Line<N>, Error> {
+        if !(1..=N).contains(&size) {
+            return Err(Error::LineLength(size))
+        }
+        let last = size as i32 - 1;
+        let (end_row, end_col) = (row as i32 + last * rstride, col as i32 + last * cstride);
+        if row >= N || col >= N || end_row < 0 || end_row >= N as i32
+                || end_col < 0 || end_col >= N as i32 {
+            return Err(Error::OutOfBounds(row, col))
+        }
+        let mut line = Self::empty(size);
+        for i in 0..size {
+            let val = board.get(
+                (row as i32 + (i as i32 * rstride)) as usize, 
+                (col as i32 + (i as i32 * cstride)) as usize
+            );
+            line.set(i, val);
+        }
+        Ok(line)
+    }
+}
+
+impl Line {
+    // primarily for testing, at least for now
+    pub fn of(s:&str) -> Line {
+        Line::try_of(s).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // as for of, through the TryFrom parser but reporting the crate's Error
+    pub fn try_of(s:&str) -> Result<Line, Error> {
+        Line::try_from(s).map_err(|e| match e {
+            LineParseError::Width(width) => Error::LineLength(width),
+            LineParseError::BadGlyph { index, glyph } => Error::BadGlyph(index, glyph),
+        })
+    }
+}
+
+impl<const N: usize> Line<N> {
+    fn set(&mut self, index:usize, val:Piece) {
+        assert!(index < self.size);
+        self.cells[index] = val
+    }
+    
+    pub fn get(&self, index:usize) -> Piece {
+        self.cells[index]
+    }
+    
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // just the cells in the line, without the padding after them
+    pub fn as_slice(&self) -> &[Piece] {
+        &self.cells[..self.size]
+    }
+
+    /**
+     * Count the line's stones of each color, a cheap test before
+     * matching patterns: a line without WIN_LENGTH cells free of one
+     * color can't hold a threat for the other.
+     */
+    pub fn summary(&self) -> LineSummary {
+        let mut summary = LineSummary { black: 0, white: 0, empty: 0 };
+        for &piece in self.as_slice() {
+            match piece {
+                Piece::Black => summary.black += 1,
+                Piece::White => summary.white += 1,
+                Piece::Empty => summary.empty += 1,
+            }
+        }
+        summary
+    }
+
+    /**
+     * Every run of n consecutive cells in the line, from the start; none
+     * if the line is shorter than n. With n = WIN_LENGTH these are the
+     * windows a six could be made in.
+     */
+    pub fn windows(&self, n:usize) -> impl Iterator<Item = &[Piece]> + '_ {
+        assert!(n > 0);
+        self.as_slice().windows(n)
+    }
+
+    // the most of piece's stones next to one another anywhere in the line
+    pub fn longest_run(&self, piece:Piece) -> usize {
+        let (mut run, mut longest) = (0, 0);
+        for &cell in self.as_slice() {
+            run = if cell == piece { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        longest
+    }
+
+    /**
+     * Count the WIN_LENGTH windows holding exactly total stones of piece
+     * and none of the opponent's, with at most max_gaps empty cells
+     * between the first and last of those stones. For example XX-XXX
+     * is a one-gap five which wins when the gap is filled.
+     */
+    pub fn count_broken(&self, piece:Piece, total:usize, max_gaps:usize) -> usize {
+        assert!(piece != Piece::Empty);
+        if self.size < WIN_LENGTH {
+            return 0
+        }
+        (0..self.size - WIN_LENGTH + 1).filter(|&start| {
+            let window = &self.cells[start..start + WIN_LENGTH];
+            if window.iter().any(|&p| p != piece && p != Piece::Empty) {
+                return false
+            }
+            let stones:Vec<usize> = (0..WIN_LENGTH).filter(|&i| window[i] == piece).collect();
+            match (stones.first(), stones.last()) {
+                (Some(&first), Some(&last)) =>
+                    stones.len() == total && last - first + 1 - total <= max_gaps,
+                _ => total == 0,
+            }
+        }).count()
+    }
+}
+
+/**
+ * Parse a line the way Line::of does.
+ */
+impl<'a> TryFrom<&'a str> for Line {
+    type Error = LineParseError;
+
+    fn try_from(s:&'a str) -> Result<Line, LineParseError> {
+        let width = s.chars().count();
+        if !(1..=BOARD_SIZE).contains(&width) {
+            return Err(LineParseError::Width(width))
+        }
+        let mut line = Line::empty(width);
+        for (index, glyph) in s.chars().enumerate() {
+            let piece = match glyph {
+                '-' => Piece::Empty,
+                'O' => Piece::White,
+                'X' => Piece::Black,
+                _ => return Err(LineParseError::BadGlyph { index, glyph })
+            };
+            line.set(index, piece);
+        }
+        Ok(line)
+    }
+}
+
+/**
+ * Draws the line as e.g. [-XO], or without the brackets as -XO with the
+ * alternate flag, {:#}. Width, fill and alignment apply to the whole.
+ */
+impl fmt::Display for Line {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::with_capacity(self.size() + 2);
+        if !fmt.alternate() {
+            s.push('[');
+        }
+        for i in 0..self.size() {
+	        match self.get(i) {
+		        Piece::Empty => s.push('-'),
+	            Piece::White => s.push('O'),
+                Piece::Black => s.push('X'),
+            }
+        }
+        if !fmt.alternate() {
+            s.push(']');
+        }
+        fmt.pad(&s)
+    }
+}
+
+/**
+ * Draw the board as BOARD_SIZE lines of -, O and X, with the highest row
+ * at the top, as FromStr reads it. The alternate form {:#} adds the row
+ * numbers at the left and the column letters underneath, as used by
+ * coord::parse_coord.
+ */
+impl fmt::Display for Board {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let labels = fmt.alternate();
+        for r in (0..BOARD_SIZE).rev() {
+            if labels {
+                write!(fmt, "{:>2} ", r + 1)?;
+            }
+            for c in 0..BOARD_SIZE {
+                fmt.write_str(match self.get(r, c) {
+                    Piece::Empty => "-",
+                    Piece::Black => "X",
+                    Piece::White => "O",
+                })?;
+            }
+            fmt.write_str("\n")?;
+        }
+        if labels {
+            let letters:String = (0..BOARD_SIZE).map(column_letter).collect();
+            writeln!(fmt, "   {}", letters)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Parse a board drawn as BOARD_SIZE lines of BOARD_SIZE cells, each -, O
+ * or X as for Line::of, with the highest row at the top.
+ */
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(s:&str) -> Result<Board, ParseError> {
+        let lines:Vec<&str> = s.lines().collect();
+        if lines.len() != BOARD_SIZE {
+            return Err(ParseError::LineCount { expected: BOARD_SIZE, found: lines.len() })
+        }
+        let mut board = Board::empty();
+        for (l, s) in lines.iter().enumerate() {
+            let width = s.chars().count();
+            if width != BOARD_SIZE {
+                return Err(ParseError::LineWidth { line: l, width })
+            }
+            let line = Line::try_from(*s).map_err(|e| match e {
+                LineParseError::Width(width) => ParseError::LineWidth { line: l, width },
+                LineParseError::BadGlyph { index, glyph } => ParseError::BadGlyph { line: l, index, glyph },
+            })?;
+            for c in 0..BOARD_SIZE {
+                board.set(BOARD_SIZE - 1 - l, c, line.get(c));
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PositionError::Unreachable { black, white } =>
+                write!(fmt, "{} black and {} white stones can't arise in a game", black, white),
+            PositionError::TwoWinners => fmt.write_str("both colors have six in a row"),
+            PositionError::WrongWinner(piece) =>
+                write!(fmt, "{:?} has won, but didn't place the last stone", piece),
+        }
+    }
+}
+
+impl fmt::Display for LenError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "expected {} cells, found {}", BOARD_SIZE * BOARD_SIZE, self.0)
+    }
+}
+
+impl fmt::Display for ConflictError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "the boards hold different stones at ({}, {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PlaceError::OutOfBounds(row, col) => write!(fmt, "({}, {}) is off the board", row, col),
+            PlaceError::Occupied { row, col, existing } =>
+                write!(fmt, "({}, {}) already holds a {:?} stone", row, col, existing),
+            PlaceError::NoStone(row, col) => write!(fmt, "Empty is not a stone to place at ({}, {})", row, col),
+        }
+    }
+}
+
+impl fmt::Display for BitsetError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitsetError::Overlap(row, col) =>
+                write!(fmt, "({}, {}) is set for both black and white", row, col),
+            BitsetError::OutOfRange(bit) =>
+                write!(fmt, "bit {} is off the {}x{} board", bit, BOARD_SIZE, BOARD_SIZE),
+        }
+    }
+}
+
+impl fmt::Display for LineParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LineParseError::Width(width) =>
+                write!(fmt, "a line of {} cells doesn't fit the board", width),
+            LineParseError::BadGlyph { index, glyph } =>
+                write!(fmt, "unexpected '{}' at index {}", glyph, index),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::LineCount { expected, found } =>
+                write!(fmt, "expected {} lines, found {}", expected, found),
+            ParseError::LineWidth { line, width } =>
+                write!(fmt, "line {} has the wrong width ({})", line, width),
+            ParseError::BadGlyph { line, index, glyph } =>
+                write!(fmt, "unexpected '{}' at line {}, index {}", glyph, line, index),
+        }
+    }
+}
+
+// where LineIterator has got to on a Board<N>
+#[derive(PartialEq, Debug)]
+enum State<const N: usize> {
+    Row(usize),
+    Col(usize),
+    DownDiag(usize, usize), 
+    UpDiag(usize, usize),
+    Finished,
+}
+
+/**
+ * A line read straight from the board it borrows: each cell's coordinates
+ * are worked out when it is asked for, so unlike a Line nothing is copied.
+ */
+#[derive(Copy, Clone)]
+pub struct LineView<'a, const N: usize = BOARD_SIZE> {
+    board : &'a Board<N>,
+    // the first cell, and the step from each cell to the next
+    row : usize,
+    col : usize,
+    rstride : i32,
+    cstride : i32,
+    size : usize,
+}
+
+impl<'a, const N: usize> LineView<'a, N> {
+    pub fn get(&self, index:usize) -> Piece {
+        let (row, col) = self.cell(index);
+        self.board.get(row, col)
+    }
+
+    // the board coordinates of the cell at index
+    pub fn cell(&self, index:usize) -> (usize, usize) {
+        assert!(index < self.size);
+        (
+            (self.row as i32 + index as i32 * self.rstride) as usize,
+            (self.col as i32 + index as i32 * self.cstride) as usize
+        )
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // a copy of the cells
+    pub fn to_line(&self) -> Line<N> {
+        Line::on(self.board, self.row, self.col, self.rstride, self.cstride, self.size)
+    }
+}
+
+struct LineIterator<'a, const N: usize> {
+    board:&'a Board<N>,
+    state:State<N>,
+}
+
+impl<'a, const N: usize> LineIterator<'a, N> {
+    fn on(board:&Board<N>) -> LineIterator<'_, N> {
+        LineIterator { board, state: State::Row(0) }
+    }
+
+    fn next_view(&mut self) -> Option<LineView<'a, N>> {
+        let result = match self.state {
+            State::Row(r) => Some(self.board.get_row(r)),
+            State::Col(c) => Some(self.board.get_col(c)),
+            State::UpDiag(r, c) => Some(self.board.get_up_diagonal(r, c)),
+            State::DownDiag(r, c) => Some(self.board.get_down_diagonal(r, c)),
+            State::Finished => None
+        };
+        self.state = self.state.next();
+        result
+    }
+}
+
+impl<'a, const N: usize> Iterator for LineIterator<'a, N> {
+    type Item = Line<N>;
+    
+    fn next(&mut self) -> Option<Line<N>> {
+        self.next_view().map(|view| view.to_line())
+    }
+}
+
+impl<const N: usize> State<N> {
+    fn next(&self) -> State<N> {
+        self.try_next().unwrap_or_else(|e| panic!("{} {:?}", e, self))
+    }
+
+    fn try_next(&self) -> Result<State<N>, Error> {
+        let max = N - 1;
+        // every arm's pattern and guard is disjoint from the others, so
+        // the order of the arms doesn't matter
+        Ok(match *self {
+            // 0->max
+            State::Row(i) if i < max  => State::Row(i+1),
+            State::Row(i) if i == max => State::Col(0),
+
+            // 0->max
+            State::Col(i) if i < max  => State::Col(i+1),
+            State::Col(i) if i == max => State::UpDiag(0, 0),
+
+            // (0,0)->(max,0)->(max,max)
+            State::UpDiag(r, 0) if r < max               => State::UpDiag(r+1, 0),
+            State::UpDiag(r, c) if r == max && c < max   => State::UpDiag(max, c+1),
+            State::UpDiag(r, c) if r == max && c == max  => State::DownDiag(0, max),
+
+            // (0,max)->(0,0)->(max,0)
+            State::DownDiag(0, c) if c > 0               => State::DownDiag(0, c-1),
+            State::DownDiag(r, 0) if r < max             => State::DownDiag(r+1, 0),
+            State::DownDiag(r, 0) if r == max            => State::Finished,
+
+            // terminal
+            State::Finished => State::Finished,
+
+            // off the traversal path, e.g. an interior diagonal start
+            _ => return Err(Error::IllegalState)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::line_count;
+    use super::StandardBoard as Board;
+    use super::Piece;
+    use super::Line;
+    use super::LineIterator;
+    use super::LineSummary;
+    use super::ParseError;
+    use super::LineParseError;
+    use super::LenError;
+    use super::BitsetError;
+    use super::PlaceError;
+    use super::ConflictError;
+    use super::PositionError;
+    use error::Error;
+    use std::convert::TryFrom;
+    use super::chebyshev_distance;
+    use super::is_connected_pair;
+    use super::cell_kind;
+    use super::lines_count_through;
+    use super::windows_through;
+    use super::CellKind;
+    use board::CENTER;
+    use board::BOARD_SIZE;
+    use board::WIN_LENGTH;
+    use board::LINE_COUNT;
+    use board::DIRECTIONS;
+
+    type State = super::State<BOARD_SIZE>;
+
+    #[test]
+    fn test_get() {
+        let b = Board::empty();
+        assert_eq!(b.get(0, 0), Piece::Empty);        
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                assert_eq!(b.get(r, c), Piece::Empty);
+            }
+        }
+    }
+    
+    #[test]
+    #[allow(clippy::manual_range_contains)]
+    fn test_set() {
+        let mut b = Board::empty();
+        b.set(CENTER,   CENTER,   Piece::Black);
+        b.set(CENTER+1, CENTER+1, Piece::White);
+        b.set(CENTER,   CENTER-1, Piece::White);
+        let b = b;
+        
+        // check the nine center pieces
+        // x==1
+        assert_eq!(b.get(CENTER+1, CENTER-1), Piece::Empty);
+        assert_eq!(b.get(CENTER+1, CENTER  ), Piece::Empty);
+        assert_eq!(b.get(CENTER+1, CENTER+1), Piece::White);
+        // x==0
+        assert_eq!(b.get(CENTER  , CENTER-1), Piece::White);
+        assert_eq!(b.get(CENTER  , CENTER  ), Piece::Black);
+        assert_eq!(b.get(CENTER  , CENTER+1), Piece::Empty);
+        // x==-1
+        assert_eq!(b.get(CENTER-1, CENTER-1), Piece::Empty);
+        assert_eq!(b.get(CENTER-1, CENTER  ), Piece::Empty);
+        assert_eq!(b.get(CENTER-1, CENTER+1), Piece::Empty);
+        // check the rest of the board
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                if (r > CENTER+1 || r < CENTER-1) && (c > CENTER+1 || c < CENTER-1) {
+                    assert_eq!(b.get(r, c), Piece::Empty);
+                }        
+            }
+        }
+    }
+    
+    #[test]
+    fn test_get_down_diagonal() {
+        let b = Board::empty();
+        
+        assert_eq!(b.get_down_diagonal(0, 0).size(), BOARD_SIZE);
+        assert_eq!(b.get_down_diagonal(1, 0).size(), BOARD_SIZE-1);
+        assert_eq!(b.get_down_diagonal(0, 1).size(), BOARD_SIZE-1);
+        assert_eq!(b.get_down_diagonal(BOARD_SIZE-1, 0).size(), 1);
+    }
+    
+    #[test]
+    fn test_get_up_diagonal() {
+        let b = Board::empty();
+        
+        assert_eq!(b.get_up_diagonal(0, 0).size(), 1);
+        assert_eq!(b.get_up_diagonal(1, 0).size(), 2);
+        assert_eq!(b.get_up_diagonal(BOARD_SIZE-1, 0).size(), BOARD_SIZE);
+        assert_eq!(b.get_up_diagonal(BOARD_SIZE-1, BOARD_SIZE-1).size(), 1);
+    }
+    
+    #[test]
+    fn test_state_transition() {
+        let mut s = State::Row(0);
+        let mut rows = 0;
+        let mut cols = 0;
         let mut d1 = 0;
         let mut d2 = 0;
         
@@ -317,23 +1734,1233 @@ mod test {
             }
             s = s.next();
         }
-        
-        assert_eq!(rows, BOARD_SIZE);
-        assert_eq!(cols, BOARD_SIZE);
-        assert_eq!(d1, BOARD_SIZE * 2 - 1);
-        assert_eq!(d2, BOARD_SIZE * 2 - 1);        
+        
+        assert_eq!(rows, BOARD_SIZE);
+        assert_eq!(cols, BOARD_SIZE);
+        assert_eq!(d1, BOARD_SIZE * 2 - 1);
+        assert_eq!(d2, BOARD_SIZE * 2 - 1);        
+    }
+    
+    #[test]
+    fn test_line_to_string() {
+        assert_eq!(
+            Line::of("---").to_string(), 
+            "[---]");
+        assert_eq!(
+            Line::of("---XX-----").to_string(), 
+            "[---XX-----]");
+        assert_eq!(
+            Line::of("---XXO--OOOO--").to_string(), 
+            "[---XXO--OOOO--]");
+    }
+
+    #[test]
+    fn test_ascii_grid_round_trip() {
+        let mut b = Board::empty();
+        b.set(0, 0, Piece::Black);
+        b.set(CENTER, CENTER, Piece::White);
+        b.set(BOARD_SIZE-1, 3, Piece::Black);
+        let grid = b.to_ascii_grid();
+        assert!(grid.starts_with("+-+-+-X-+"));
+        let parsed = Board::from_ascii_grid(&grid).unwrap();
+        assert_eq!(parsed.to_ascii_grid(), grid);
+        assert_eq!(parsed.get(0, 0), Piece::Black);
+        assert_eq!(parsed.get(CENTER, CENTER), Piece::White);
+        assert_eq!(parsed.get(BOARD_SIZE-1, 3), Piece::Black);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let empty_row = "-".repeat(BOARD_SIZE);
+        let empty = vec![empty_row.as_str(); BOARD_SIZE].join("\n");
+        let b:Board = empty.parse().unwrap();
+        assert_eq!(b.empty_count(), BOARD_SIZE * BOARD_SIZE);
+
+        // the top line is the highest row
+        let top = format!("X{}O", "-".repeat(BOARD_SIZE - 2));
+        let bottom = format!("{}X", "-".repeat(BOARD_SIZE - 1));
+        let mut lines = vec![empty_row.as_str(); BOARD_SIZE];
+        lines[0] = &top;
+        lines[BOARD_SIZE - 1] = &bottom;
+        let b:Board = lines.join("\n").parse().unwrap();
+        assert_eq!(b.get(BOARD_SIZE - 1, 0), Piece::Black);
+        assert_eq!(b.get(BOARD_SIZE - 1, BOARD_SIZE - 1), Piece::White);
+        assert_eq!(b.get(0, BOARD_SIZE - 1), Piece::Black);
+        assert_eq!(b.count(Piece::Black) + b.count(Piece::White), 3);
+
+        assert_eq!(lines[1..].join("\n").parse::<Board>().err(), Some(ParseError::LineCount { expected: BOARD_SIZE, found: BOARD_SIZE - 1 }));
+        let short = &empty_row[1..];
+        lines[2] = short;
+        assert_eq!(lines.join("\n").parse::<Board>().err(), Some(ParseError::LineWidth { line: 2, width: BOARD_SIZE - 1 }));
+        let bad = format!("{}+", short);
+        lines[2] = &bad;
+        assert_eq!(lines.join("\n").parse::<Board>().err(),
+            Some(ParseError::BadGlyph { line: 2, index: BOARD_SIZE - 1, glyph: '+' }));
+    }
+
+    #[test]
+    fn test_board_display() {
+        let empty_row = "-".repeat(BOARD_SIZE);
+        let mut b = Board::empty();
+        assert_eq!(b.to_string(), format!("{}\n", empty_row).repeat(BOARD_SIZE));
+
+        b.set(CENTER, CENTER, Piece::Black);
+        let center_row = format!("{}X{}\n", "-".repeat(CENTER), "-".repeat(BOARD_SIZE - CENTER - 1));
+        let above = format!("{}\n", empty_row).repeat(BOARD_SIZE - 1 - CENTER);
+        let below = format!("{}\n", empty_row).repeat(CENTER);
+        assert_eq!(b.to_string(), format!("{}{}{}", above, center_row, below));
+
+        b.set(0, 1, Piece::White);
+        let labeled = format!("{:#}", b);
+        let lines:Vec<&str> = labeled.lines().collect();
+        assert_eq!(lines.len(), BOARD_SIZE + 1);
+        assert_eq!(lines[0], format!("19 {}", empty_row));
+        assert_eq!(lines[BOARD_SIZE - 1], format!(" 1 -O{}", &empty_row[2..]));
+        assert_eq!(lines[BOARD_SIZE], "   ABCDEFGHJKLMNOPQRST");
+
+        // without the labels it parses back
+        let stripped:Vec<&str> = lines[..BOARD_SIZE].iter().map(|line| &line[3..]).collect();
+        let parsed:Board = stripped.join("\n").parse().unwrap();
+        assert_eq!(parsed.to_string(), b.to_string());
+        assert_eq!(b.to_string().parse::<Board>().unwrap().zobrist(), b.zobrist());
+    }
+
+    #[test]
+    fn test_vec_round_trip() {
+        let mut b = Board::empty();
+        b.set(0, 1, Piece::Black);
+        b.set(1, 0, Piece::White);
+        let v = b.to_vec();
+        assert_eq!(v.len(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(v[1], Piece::Black);
+        assert_eq!(v[BOARD_SIZE], Piece::White);
+        assert_eq!(Board::from_vec(&v).unwrap().to_vec(), v);
+        assert_eq!(Board::from_vec(&v[1..]).err(), Some(LenError(BOARD_SIZE * BOARD_SIZE - 1)));
+    }
+
+    #[test]
+    fn test_to_planes() {
+        let mut b = Board::empty();
+        b.set(0, 1, Piece::Black);
+        b.set(1, 0, Piece::White);
+        b.set(CENTER, CENTER, Piece::White);
+        let sum = |plane:&[[f32; BOARD_SIZE]; BOARD_SIZE]| -> f32 {
+            plane.iter().map(|row| row.iter().sum::<f32>()).sum()
+        };
+        let planes = b.to_planes(Piece::White);
+        assert_eq!(sum(&planes[0]), 2.0);
+        assert_eq!(sum(&planes[1]), 1.0);
+        assert_eq!(planes[1][0][1], 1.0);
+        assert_eq!(sum(&planes[2]), 0.0);
+        let planes = b.to_planes(Piece::Black);
+        assert_eq!(sum(&planes[0]) + sum(&planes[1]), 3.0);
+        assert_eq!(planes[0][0][1], 1.0);
+        assert_eq!(sum(&planes[2]), (BOARD_SIZE * BOARD_SIZE) as f32);
+    }
+
+    #[test]
+    fn test_ascii_grid_marked() {
+        let mut b = Board::empty();
+        b.set(BOARD_SIZE-1, 0, Piece::Black);
+        b.set(BOARD_SIZE-1, 1, Piece::White);
+        b.set(BOARD_SIZE-1, 2, Piece::White);
+        let grid = b.to_ascii_grid_marked(&[(BOARD_SIZE-1, 1), (BOARD_SIZE-1, 2), (0, 0)]);
+        assert!(grid.starts_with("X-o-o-+"));
+        assert!(grid.lines().last().unwrap().starts_with("+-+"));
+        let parsed = Board::from_ascii_grid(&grid).unwrap();
+        assert_eq!(parsed.to_ascii_grid(), b.to_ascii_grid());
+    }
+
+    #[test]
+    fn test_ascii_grid_errors() {
+        let empty = Board::from_ascii_grid("").err().unwrap();
+        assert_eq!(empty, ParseError::LineCount { expected: 2 * BOARD_SIZE - 1, found: 0 });
+        assert_eq!(empty.to_string(), "expected 37 lines, found 0");
+
+        let grid = Board::empty().to_ascii_grid();
+        let short = grid.replacen("+-+", "+", 1);
+        assert_eq!(
+            Board::from_ascii_grid(&short).err(),
+            Some(ParseError::LineWidth { line: 0, width: 2 * BOARD_SIZE - 3 }));
+
+        let bad = grid.replacen("+", "Z", 1);
+        assert_eq!(
+            Board::from_ascii_grid(&bad).err(),
+            Some(ParseError::BadGlyph { line: 0, index: 0, glyph: 'Z' }));
+    }
+
+    #[test]
+    fn test_min_stones_to_win() {
+        let mut b = Board::empty();
+        assert_eq!(b.min_stones_to_win(Piece::Black), Some(WIN_LENGTH));
+        for c in 0..5 {
+            b.set(CENTER, CENTER + c, Piece::Black);
+        }
+        assert_eq!(b.min_stones_to_win(Piece::Black), Some(1));
+        assert_eq!(b.min_stones_to_win(Piece::White), Some(WIN_LENGTH));
+    }
+
+    #[test]
+    fn test_has_exact_six() {
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH - 1 {
+            b.set(CENTER, c, Piece::Black);
+            b.set(c + 2, c, Piece::White);
+        }
+        assert!(!b.has_exact_six(Piece::Black));
+        b.set(CENTER, WIN_LENGTH - 1, Piece::Black);
+        b.set(WIN_LENGTH + 1, WIN_LENGTH - 1, Piece::White);
+        assert!(b.has_exact_six(Piece::Black));
+        assert!(b.has_exact_six(Piece::White));
+        b.set(CENTER, WIN_LENGTH, Piece::Black);
+        assert!(!b.has_exact_six(Piece::Black));
+        // a six bounded by the opponent still counts
+        b.set(CENTER, WIN_LENGTH, Piece::White);
+        assert!(b.has_exact_six(Piece::Black));
+    }
+
+    #[test]
+    fn test_six_windows() {
+        let b = Board::empty();
+        // 14 per row and column, and 14 + 2 * (13 + 12 + ... + 1) per diagonal direction
+        assert_eq!(b.six_windows().count(), 924);
+        for window in b.six_windows() {
+            for pair in window.windows(2) {
+                let (r0, c0) = pair[0];
+                let (r1, c1) = pair[1];
+                assert!(c1 == c0 || c1 == c0 + 1);
+                assert!(r1 == r0 || r1 == r0 + 1 || r1 + 1 == r0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_winner() {
+        // a row, a column, and a diagonal each way, the last an overline
+        let runs:[(usize, usize, i32, i32, usize); 4] = [
+            (0, 13, 0, 1, WIN_LENGTH),
+            (3, CENTER, 1, 0, WIN_LENGTH),
+            (2, 2, 1, 1, WIN_LENGTH),
+            (BOARD_SIZE - 1, 0, -1, 1, WIN_LENGTH + 1),
+        ];
+        for &(row, col, rstride, cstride, len) in runs.iter() {
+            let mut b = Board::empty();
+            let cell = |i:usize| ((row as i32 + i as i32 * rstride) as usize, (col as i32 + i as i32 * cstride) as usize);
+            for i in 0..WIN_LENGTH - 1 {
+                let (r, c) = cell(i);
+                b.set(r, c, Piece::Black);
+            }
+            assert_eq!(b.winner(), None);
+            for i in WIN_LENGTH - 1..len {
+                let (r, c) = cell(i);
+                b.set(r, c, Piece::Black);
+            }
+            assert_eq!(b.winner(), Some(Piece::Black));
+        }
+
+        // five with a gap before the sixth
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH + 1 {
+            if c != WIN_LENGTH - 1 {
+                b.set(CENTER, c, Piece::White);
+            }
+        }
+        assert_eq!(b.winner(), None);
+    }
+
+    #[test]
+    fn test_wins_after() {
+        let mut b = Board::empty();
+        for i in 0..WIN_LENGTH - 1 {
+            b.set(3 + i, 2 + i, Piece::White);
+        }
+        assert_eq!(b.winner(), None);
+        assert_eq!(b.wins_at(5, 4), None);
+
+        b.set(3 + WIN_LENGTH - 1, 2 + WIN_LENGTH - 1, Piece::White);
+        b.set(0, 0, Piece::Black);
+        assert_eq!(b.winner(), Some(Piece::White));
+        assert_eq!(b.wins_at(5, 4), b.winner());
+        assert_eq!(b.wins_at(3, 2), b.winner());
+        assert_eq!(b.wins_at(0, 0), None);
+        assert_eq!(b.wins_at(CENTER, 0), None);
+    }
+
+    #[test]
+    fn test_cells_near_stones() {
+        let mut b = Board::empty();
+        assert_eq!(b.cells_near_stones(2).count(), 0);
+
+        b.set(CENTER, CENTER, Piece::Black);
+        let near:Vec<_> = b.cells_near_stones(1).collect();
+        assert_eq!(near.len(), 8);
+        assert_eq!(near[0], (CENTER - 1, CENTER - 1));
+        assert!(near.iter().all(|&cell| chebyshev_distance(cell, (CENTER, CENTER)) == 1));
+        assert_eq!(b.cells_near_stones(0).count(), 0);
+
+        // adjacent stones, whose neighborhoods share six cells
+        b.set(CENTER, CENTER + 1, Piece::White);
+        let near:Vec<_> = b.cells_near_stones(1).collect();
+        assert_eq!(near.len(), 10);
+        let mut sorted = near.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, near);
+        assert!(near.iter().all(|&(r, c)| b.get(r, c) == Piece::Empty));
+
+        // clipped to the board
+        b.set(0, 0, Piece::Black);
+        assert_eq!(b.cells_near_stones(1).filter(|&(r, c)| r < 2 && c < 2).count(), 3);
+        assert_eq!(b.cells_near_stones(2).count(), 5 * 6 - 2 + 9 - 1);
+    }
+
+    #[test]
+    fn test_count_in_direction() {
+        let mut b = Board::empty();
+        for c in 0..4 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        // running off the edge of the board
+        assert_eq!(b.count_in_direction(CENTER, 4, 0, -1, Piece::Black), 4);
+        assert_eq!(b.count_in_direction(CENTER, 3, 0, -1, Piece::Black), 3);
+        assert_eq!(b.count_in_direction(CENTER, 0, 0, -1, Piece::Black), 0);
+        assert_eq!(b.count_in_direction(CENTER, 4, 0, 1, Piece::Black), 0);
+        assert_eq!(b.count_in_direction(CENTER, 0, 0, 1, Piece::Black), 3);
+        assert_eq!(b.count_in_direction(CENTER, 4, 0, -1, Piece::White), 0);
+
+        // blocked by the opponent, and along a diagonal
+        b.set(CENTER, 2, Piece::White);
+        assert_eq!(b.count_in_direction(CENTER, 4, 0, -1, Piece::Black), 1);
+        assert_eq!(b.count_in_direction(CENTER, 0, 0, 1, Piece::Black), 1);
+        for i in 1..3 {
+            b.set(CENTER + i, 2 + i, Piece::White);
+        }
+        assert_eq!(b.count_in_direction(CENTER - 1, 1, 1, 1, Piece::White), 3);
+        assert_eq!(b.count_in_direction(CENTER + 3, 5, -1, -1, Piece::White), 3);
+    }
+
+    #[test]
+    fn test_wins_at() {
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH {
+            b.set(0, BOARD_SIZE - 1 - c, Piece::White);
+        }
+        assert_eq!(b.wins_at(0, BOARD_SIZE - 1), Some(Piece::White));
+        assert_eq!(b.wins_at(0, BOARD_SIZE - 3), Some(Piece::White));
+        assert_eq!(b.wins_at(1, BOARD_SIZE - 1), None);
+
+        // five for Black on either side of a White stone
+        for r in 0..WIN_LENGTH - 1 {
+            b.set(2 + r, CENTER, Piece::Black);
+        }
+        b.set(2 + WIN_LENGTH - 1, CENTER, Piece::White);
+        b.set(2 + WIN_LENGTH, CENTER, Piece::Black);
+        assert_eq!(b.wins_at(2, CENTER), None);
+        assert_eq!(b.wins_at(2 + WIN_LENGTH, CENTER), None);
+        b.set(2 + WIN_LENGTH - 1, CENTER, Piece::Black);
+        assert_eq!(b.wins_at(2 + WIN_LENGTH, CENTER), Some(Piece::Black));
+    }
+
+    #[test]
+    fn test_cell_kind() {
+        assert_eq!(cell_kind(0, 0), CellKind::Corner);
+        assert_eq!(cell_kind(BOARD_SIZE - 1, 0), CellKind::Corner);
+        assert_eq!(cell_kind(0, CENTER), CellKind::Edge);
+        assert_eq!(cell_kind(CENTER, BOARD_SIZE - 1), CellKind::Edge);
+        assert_eq!(cell_kind(CENTER, CENTER), CellKind::Interior);
+        assert_eq!(cell_kind(1, 1), CellKind::Interior);
+    }
+
+    #[test]
+    fn test_lines_count_through() {
+        assert_eq!(lines_count_through(CENTER, CENTER), 4);
+        // the up diagonal through a corner is the corner alone
+        assert_eq!(lines_count_through(0, 0), 3);
+        assert_eq!(lines_count_through(0, WIN_LENGTH - 1), 4);
+        assert_eq!(lines_count_through(0, WIN_LENGTH - 2), 3);
+        assert_eq!(lines_count_through(1, 1), 3);
+    }
+
+    #[test]
+    fn test_windows_through() {
+        let center = windows_through(CENTER, CENTER);
+        assert_eq!(center.len(), 4 * WIN_LENGTH);
+        assert!(center.iter().all(|window| window.contains(&(CENTER, CENTER))));
+        // the corner's windows start there, and none is an up diagonal
+        let corner = windows_through(0, 0);
+        assert_eq!(corner.len(), 3);
+        assert!(corner.iter().all(|window| window[0] == (0, 0)));
+        // on an edge the row has all its windows, the others one each
+        assert_eq!(windows_through(0, CENTER).len(), WIN_LENGTH + 3);
+        assert_eq!(windows_through(BOARD_SIZE - 1, CENTER).len(), WIN_LENGTH + 3);
+        let all:usize = (0..BOARD_SIZE).map(|r| (0..BOARD_SIZE).map(|c| windows_through(r, c).len()).sum::<usize>()).sum();
+        assert_eq!(all, WIN_LENGTH * Board::empty().six_windows().count());
+    }
+
+    #[test]
+    fn test_is_connected_pair() {
+        assert!(is_connected_pair((CENTER, CENTER), (CENTER, CENTER+1)));
+        assert!(is_connected_pair((CENTER, CENTER), (CENTER-1, CENTER)));
+        assert!(is_connected_pair((CENTER, CENTER), (CENTER+1, CENTER+1)));
+        assert!(is_connected_pair((1, 1), (0, 2)));
+        assert!(!is_connected_pair((CENTER, CENTER), (CENTER, CENTER)));
+        assert!(!is_connected_pair((CENTER, CENTER), (CENTER, CENTER+2)));
+        assert!(!is_connected_pair((0, 0), (BOARD_SIZE-1, BOARD_SIZE-1)));
+    }
+
+    #[test]
+    fn test_line_between() {
+        let mut b = Board::empty();
+        b.set(2, 7, Piece::Black);
+        b.set(6, 3, Piece::White);
+        let line = b.line_between((2, 7), (6, 3)).unwrap();
+        assert_eq!(line.to_string(), "[X---O]");
+        let line = b.line_between((6, 3), (2, 7)).unwrap();
+        assert_eq!(line.to_string(), "[O---X]");
+        assert_eq!(b.line_between((2, 7), (2, 7)).unwrap().size(), 1);
+        assert_eq!(b.line_between((0, 0), (0, BOARD_SIZE-1)).unwrap().size(), BOARD_SIZE);
+        assert!(b.line_between((2, 7), (6, 4)).is_none());
+        assert!(b.line_between((0, 0), (1, 2)).is_none());
+    }
+
+    #[test]
+    fn test_count() {
+        let mut b = Board::empty();
+        assert_eq!(b.count(Piece::Empty), BOARD_SIZE * BOARD_SIZE);
+        b.set(0, 0, Piece::Black);
+        b.set(0, 1, Piece::Black);
+        b.set(1, 0, Piece::White);
+        assert_eq!(b.count(Piece::Black), 2);
+        assert_eq!(b.count(Piece::White), 1);
+        assert_eq!(b.count(Piece::Empty), BOARD_SIZE * BOARD_SIZE - 3);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut b = Board::empty();
+        assert_eq!(b.centroid(Piece::Black), None);
+        b.set(CENTER - 2, CENTER + 3, Piece::Black);
+        b.set(CENTER + 2, CENTER - 3, Piece::Black);
+        b.set(0, 0, Piece::White);
+        assert_eq!(b.centroid(Piece::Black), Some((CENTER as f64, CENTER as f64)));
+        assert_eq!(b.centroid(Piece::White), Some((0.0, 0.0)));
+        b.set(0, 1, Piece::White);
+        assert_eq!(b.centroid(Piece::White), Some((0.0, 0.5)));
+    }
+
+    #[test]
+    fn test_board_eq() {
+        use std::collections::HashSet;
+
+        let mut a = Board::empty();
+        let mut b = Board::empty();
+        a.set(CENTER, CENTER, Piece::Black);
+        a.set(0, 0, Piece::White);
+        b.set(0, 0, Piece::White);
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(a == b);
+        let mut set = HashSet::new();
+        set.insert(a);
+        assert!(set.contains(&b));
+
+        b.set(0, 1, Piece::White);
+        assert!(a != b);
+        assert!(!set.contains(&b));
+        // taking the stone away again restores equality
+        b.set(0, 1, Piece::Empty);
+        assert!(a == b);
+    }
+
+    #[test]
+    fn test_verify_hash() {
+        let mut b = Board::empty();
+        assert!(b.verify_hash());
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 0, Piece::Black);
+        b.set(CENTER, CENTER, Piece::Empty);
+        assert!(b.verify_hash());
+        assert!(Board::from_vec(&b.to_vec()).unwrap().verify_hash());
+        assert_eq!(Board::from_vec(&b.to_vec()).unwrap().zobrist(), b.zobrist());
+
+        b.hash ^= 1;
+        assert!(!b.verify_hash());
+    }
+
+    #[test]
+    fn test_best_window_for() {
+        let mut b = Board::empty();
+        for i in 1..4 {
+            b.set(CENTER, CENTER - i, Piece::Black);
+        }
+        b.set(CENTER + 1, CENTER, Piece::Black);
+        let best = b.best_window_for(CENTER, CENTER, Piece::Black).unwrap();
+        assert!(best.contains(&(CENTER, CENTER)));
+        assert!(best.iter().all(|&(r, _)| r == CENTER));
+        assert_eq!(best.iter().filter(|&&(r, c)| b.get(r, c) == Piece::Black).count(), 3);
+        assert_eq!(best[0], (CENTER, CENTER - 5));
+
+        // blocking the row leaves the column the strongest
+        b.set(CENTER, CENTER - 4, Piece::White);
+        b.set(CENTER, CENTER + 1, Piece::White);
+        let best = b.best_window_for(CENTER, CENTER, Piece::Black).unwrap();
+        assert!(best.contains(&(CENTER + 1, CENTER)));
+        assert!(best.iter().all(|&(_, c)| c == CENTER));
+
+        assert_eq!(b.best_window_for(CENTER, CENTER - 1, Piece::Black), None);
+        let mut boxed = Board::empty();
+        for &(r, c) in [(0, 1), (1, 0), (1, 1)].iter() {
+            boxed.set(r, c, Piece::White);
+        }
+        assert_eq!(boxed.best_window_for(0, 0, Piece::Black), None);
+    }
+
+    #[test]
+    fn test_idle_stones() {
+        let mut b = Board::empty();
+        b.set(0, 0, Piece::Black);
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(b.idle_stones(Piece::Black).is_empty());
+        for &(r, c) in [(0, 1), (1, 0), (1, 1)].iter() {
+            b.set(r, c, Piece::White);
+        }
+        assert_eq!(b.idle_stones(Piece::Black), vec![(0, 0)]);
+        // White has windows clear of the corner
+        assert!(b.idle_stones(Piece::White).is_empty());
+    }
+
+    #[test]
+    fn test_empty_count() {
+        let mut b = Board::empty();
+        assert_eq!(b.empty_count(), BOARD_SIZE * BOARD_SIZE);
+        b.set(0, 0, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 1, Piece::Black);
+        assert_eq!(b.empty_count(), BOARD_SIZE * BOARD_SIZE - 2);
+        b.set(0, 0, Piece::Empty);
+        b.set(0, 2, Piece::Empty);
+        assert_eq!(b.empty_count(), b.count(Piece::Empty));
+        assert!(!b.is_full());
+
+        let full = Board::from_vec(&[Piece::Black; BOARD_SIZE * BOARD_SIZE]).unwrap();
+        assert_eq!(full.empty_count(), 0);
+        assert!(full.is_full());
+        assert_eq!(Board::from_vec(&b.to_vec()).unwrap().empty_count(), b.empty_count());
+    }
+
+    #[test]
+    fn test_center_region() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 2, CENTER - 2, Piece::White);
+        assert_eq!(b.center_region(0).collect::<Vec<_>>(), vec![(CENTER, CENTER, Piece::Black)]);
+        assert_eq!(b.center_region(1).count(), 9);
+        assert_eq!(b.center_region(2).count(), 25);
+        assert_eq!(b.center_region(2).filter(|&(_, _, piece)| piece == Piece::White).count(), 1);
+        assert_eq!(b.center_region(CENTER).count(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(b.center_region(BOARD_SIZE).count(), BOARD_SIZE * BOARD_SIZE);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        let mut a = Board::empty();
+        a.set(CENTER, CENTER, Piece::Black);
+        a.set(0, 0, Piece::White);
+        let mut b = a;
+        assert_eq!(a.hamming_distance(&b), 0);
+        b.set(CENTER, CENTER, Piece::White);
+        assert_eq!(a.hamming_distance(&b), 1);
+        b.set(0, 0, Piece::Empty);
+        b.set(1, 1, Piece::Black);
+        assert_eq!(a.hamming_distance(&b), 3);
+        assert_eq!(b.hamming_distance(&a), 3);
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut a = Board::empty();
+        a.set(CENTER, CENTER, Piece::Black);
+        a.set(0, 0, Piece::White);
+        let mut b = a;
+        assert_eq!(a.diff(&b), vec![]);
+
+        b.set(CENTER + 1, CENTER, Piece::White);
+        assert_eq!(a.diff(&b), vec![(CENTER + 1, CENTER, Piece::White)]);
+        assert_eq!(b.diff(&a), vec![(CENTER + 1, CENTER, Piece::Empty)]);
+
+        b.set(0, 0, Piece::Empty);
+        b.set(CENTER, CENTER, Piece::White);
+        let diff = a.diff(&b);
+        assert_eq!(diff, vec![(0, 0, Piece::Empty), (CENTER, CENTER, Piece::White), (CENTER + 1, CENTER, Piece::White)]);
+        assert_eq!(diff.len(), a.hamming_distance(&b));
+        for (r, c, piece) in diff {
+            a.set(r, c, piece);
+        }
+        assert!(a == b);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut b = Board::empty();
+        assert_eq!(b.validate(), Ok(()));
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 1, Piece::White);
+        assert_eq!(b.validate(), Ok(()));
+        b.set(0, 2, Piece::White);
+        assert_eq!(b.validate(), Err(PositionError::Unreachable { black: 1, white: 3 }));
+
+        // White's six in a row came with the last stone
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH {
+            b.set(0, c, Piece::White);
+        }
+        for c in 0..5 {
+            b.set(CENTER, 2 * c, Piece::Black);
+        }
+        assert_eq!(b.validate(), Ok(()));
+
+        // Black has won, but seven black stones means White moved last
+        let mut b = Board::empty();
+        for c in 0..7 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        for c in 0..8 {
+            b.set(0, 2 * c, Piece::White);
+        }
+        assert_eq!(b.validate(), Err(PositionError::WrongWinner(Piece::Black)));
+        for c in 0..8 {
+            b.set(0, 2 * c, Piece::Empty);
+            b.set(c / 6, c % 6, Piece::White);
+        }
+        assert_eq!(b.validate(), Err(PositionError::TwoWinners));
+    }
+
+    #[test]
+    fn test_orthogonals_and_diagonals() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.orthogonals().count(), 38);
+        assert_eq!(b.diagonals().count(), 74);
+        assert!(b.orthogonals().all(|line| line.size() == BOARD_SIZE));
+        assert_eq!(b.diagonals().filter(|line| line.size() == 1).count(), 4);
+        assert_eq!(b.orthogonals().filter(|line| line.count_broken(Piece::Black, 1, 0) > 0).count(), 2);
+        assert_eq!(b.diagonals().filter(|line| line.count_broken(Piece::Black, 1, 0) > 0).count(), 2);
+    }
+
+    #[test]
+    fn test_live_windows() {
+        let mut b = Board::empty();
+        let all = b.six_windows().count();
+        assert_eq!(b.live_windows(Piece::Black), all);
+        b.set(0, 0, Piece::White);
+        assert_eq!(b.live_windows(Piece::Black), all - 3);
+        assert_eq!(b.live_windows(Piece::White), all);
+        b.set(CENTER, CENTER, Piece::White);
+        assert_eq!(b.live_windows(Piece::Black), all - 3 - 4 * WIN_LENGTH);
+    }
+
+    #[test]
+    fn test_fill_ratio() {
+        let mut b = Board::empty();
+        assert_eq!(b.fill_ratio(), 0.0);
+        for c in 0..BOARD_SIZE {
+            b.set(0, c, Piece::Black);
+        }
+        assert_eq!(b.fill_ratio(), 1.0 / BOARD_SIZE as f64);
+        for r in 1..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                b.set(r, c, Piece::White);
+            }
+        }
+        assert_eq!(b.fill_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_infer_to_move() {
+        let mut b = Board::empty();
+        assert_eq!(b.infer_to_move(), Some(Piece::Black));
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.infer_to_move(), Some(Piece::White));
+        b.set(CENTER, CENTER+1, Piece::White);
+        assert_eq!(b.infer_to_move(), None);
+        b.set(CENTER, CENTER+2, Piece::White);
+        assert_eq!(b.infer_to_move(), Some(Piece::Black));
+        b.set(0, 0, Piece::Black);
+        b.set(0, 1, Piece::Black);
+        assert_eq!(b.infer_to_move(), Some(Piece::White));
+        b.set(0, 2, Piece::Black);
+        assert_eq!(b.infer_to_move(), None);
+    }
+
+    #[test]
+    fn test_move_number() {
+        let mut b = Board::empty();
+        assert_eq!(b.move_number(), 0);
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.move_number(), 1);
+        b.set(CENTER, CENTER+1, Piece::White);
+        assert_eq!(b.move_number(), 2);
+        b.set(CENTER, CENTER+2, Piece::White);
+        assert_eq!(b.move_number(), 2);
+        b.set(0, 0, Piece::Black);
+        b.set(0, 1, Piece::Black);
+        assert_eq!(b.move_number(), 3);
+    }
+
+    #[test]
+    fn test_stones() {
+        let mut b = Board::empty();
+        assert_eq!(b.stones().count(), 0);
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 5, Piece::White);
+        b.set(CENTER, 0, Piece::White);
+        assert_eq!(b.stones().collect::<Vec<_>>(), vec![
+            (0, 5, Piece::White),
+            (CENTER, 0, Piece::White),
+            (CENTER, CENTER, Piece::Black),
+        ]);
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        let mut b = Board::empty();
+        assert_eq!(b.empty_cells().count(), 361);
+        assert_eq!(b.stone_count(), (0, 0));
+        b.set(0, 0, Piece::Black);
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 2, Piece::White);
+        let empty:Vec<(usize, usize)> = b.empty_cells().collect();
+        assert_eq!(empty.len(), b.empty_count());
+        assert_eq!(&empty[..2], &[(0, 1), (0, 3)]);
+        assert!(!empty.contains(&(CENTER, CENTER)));
+        assert_eq!(b.stone_count(), (2, 1));
+    }
+
+    #[test]
+    fn test_count_broken() {
+        let line = Line::of("XX-XXX");
+        assert_eq!(line.count_broken(Piece::Black, 5, 1), 1);
+        assert_eq!(line.count_broken(Piece::Black, 5, 0), 0);
+        assert_eq!(line.count_broken(Piece::White, 5, 1), 0);
+        assert_eq!(Line::of("-XX-XX-").count_broken(Piece::Black, 4, 1), 2);
+        assert_eq!(Line::of("OXX-XXX").count_broken(Piece::Black, 5, 1), 1);
+        assert_eq!(Line::of("XX-XX").count_broken(Piece::Black, 4, 1), 0);
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let mut b = Board::empty();
+        assert!(b.is_reachable());
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(b.is_reachable());
+        b.set(0, 0, Piece::White);
+        assert!(b.is_reachable());
+        b.set(0, 1, Piece::White);
+        assert!(b.is_reachable());
+        b.set(0, 2, Piece::White);
+        assert!(!b.is_reachable());
+
+        let mut b = Board::empty();
+        b.set(0, 0, Piece::White);
+        assert!(!b.is_reachable());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let mut rng = StdRng::seed_from_u64(6);
+        for &stones in [0, 1, 2, 3, 50, BOARD_SIZE * BOARD_SIZE].iter() {
+            let b = Board::random(&mut rng, stones);
+            assert_eq!(b.count(Piece::Black) + b.count(Piece::White), stones);
+            assert!(b.is_reachable());
+        }
+    }
+
+    #[test]
+    fn test_state_transition_exhaustive() {
+        let mut s = State::Row(0);
+        let mut states = 0;
+        while s != State::Finished {
+            states += 1;
+            assert!(states <= 112, "traversal did not terminate");
+            s = s.next();
+        }
+        assert_eq!(states, 112);
+        assert_eq!(s.next(), State::Finished);
     }
-    
+
     #[test]
-    fn test_line_to_string() {
+    fn test_line_display() {
+        let line = Line::of("-XO");
+        assert_eq!(format!("{}", line), "[-XO]");
+        assert_eq!(format!("{:#}", line), "-XO");
+        assert_eq!(format!("{:>7}", line), "  [-XO]");
+        assert_eq!(format!("{:.<#5}", line), "-XO..");
+    }
+
+    #[test]
+    fn test_line_as_slice() {
+        let line = Line::of("-XO");
+        assert_eq!(line.as_slice(), &[Piece::Empty, Piece::Black, Piece::White]);
+        let b = Board::empty();
+        for i in 0..LINE_COUNT {
+            let line = b.line(i);
+            assert_eq!(line.as_slice().len(), line.size());
+        }
+        for (i, &piece) in line.as_slice().iter().enumerate() {
+            assert_eq!(piece, line.get(i));
+        }
+    }
+
+    #[test]
+    fn test_line_summary() {
+        assert_eq!(Line::of("-XO-X").summary(), LineSummary { black: 2, white: 1, empty: 2 });
+        let b = Board::empty();
+        assert_eq!(b.line(0).summary(), LineSummary { black: 0, white: 0, empty: BOARD_SIZE });
+        for s in ["X", "OOOOOO", "--XXO-X-O"].iter() {
+            let line = Line::of(s);
+            let summary = line.summary();
+            assert_eq!(summary.black + summary.white + summary.empty, line.size());
+        }
+    }
+
+    #[test]
+    fn test_line_windows() {
+        let six = Line::of("-XXXXO");
+        assert_eq!(six.windows(WIN_LENGTH).collect::<Vec<_>>(), vec![six.as_slice()]);
+        assert_eq!(Line::of("XXXXX").windows(WIN_LENGTH).count(), 0);
+        let row = Board::empty().line(0);
+        assert_eq!(row.windows(WIN_LENGTH).count(), BOARD_SIZE - WIN_LENGTH + 1);
+        assert!(row.windows(WIN_LENGTH).all(|w| w.len() == WIN_LENGTH));
+        let short = Line::of("X-O");
+        let windows:Vec<&[Piece]> = short.windows(2).collect();
+        assert_eq!(windows, vec![&[Piece::Black, Piece::Empty][..], &[Piece::Empty, Piece::White][..]]);
+    }
+
+    #[test]
+    fn test_line_longest_run() {
+        assert_eq!(Line::of("XXXXXX").longest_run(Piece::Black), 6);
+        assert_eq!(Line::of("XXXXXX").longest_run(Piece::White), 0);
+        assert_eq!(Line::of("-XXX-XX-").longest_run(Piece::Black), 3);
+        assert_eq!(Line::of("OXXOXXXO").longest_run(Piece::Black), 3);
+        assert_eq!(Line::of("OXXOXXXO").longest_run(Piece::White), 1);
+        assert_eq!(Board::empty().line(0).longest_run(Piece::Black), 0);
+    }
+
+    #[test]
+    fn test_line_try_from() {
+        assert_eq!(Line::try_from("-XO-"), Ok(Line::of("-XO-")));
+        assert_eq!(Line::try_from("XXZX"), Err(LineParseError::BadGlyph { index: 2, glyph: 'Z' }));
+        assert_eq!(Line::try_from(""), Err(LineParseError::Width(0)));
+    }
+
+    #[test]
+    fn test_try_variants() {
+        let mut b = Board::empty();
+        assert_eq!(b.try_set(0, BOARD_SIZE, Piece::Black), Err(Error::OutOfBounds(0, BOARD_SIZE)));
+        assert_eq!(b.try_set(1, 2, Piece::Black), Ok(Piece::Empty));
+        assert_eq!(b.try_get(1, 2), Ok(Piece::Black));
+        assert_eq!(b.try_get(BOARD_SIZE, 0), Err(Error::OutOfBounds(BOARD_SIZE, 0)));
+        assert_eq!(b.try_line(1).map(|l| l.get(2)), Ok(Piece::Black));
+        assert_eq!(b.try_line(LINE_COUNT).err(), Some(Error::LineIndex(LINE_COUNT)));
+        assert_eq!(Line::try_of("X-O"), Ok(Line::of("X-O")));
+        assert_eq!(Line::try_of("X-Z"), Err(Error::BadGlyph(2, 'Z')));
+        assert_eq!(Line::try_of(""), Err(Error::LineLength(0)));
+        assert_eq!(Line::try_of(&"-".repeat(BOARD_SIZE + 1)), Err(Error::LineLength(BOARD_SIZE + 1)));
+        assert_eq!(Line::try_on(&b, 1, 0, 0, 1, 3), Ok(Line::of("--X")));
+        assert_eq!(Line::try_on(&b, 0, 0, 1, 1, 0), Err(Error::LineLength(0)));
+        assert_eq!(Line::try_on(&b, BOARD_SIZE, 0, 0, 1, 1), Err(Error::OutOfBounds(BOARD_SIZE, 0)));
+        assert_eq!(Line::try_on(&b, 1, 0, -1, 1, 3), Err(Error::OutOfBounds(1, 0)));
+        assert_eq!(Line::try_on(&b, 0, 1, 0, 1, BOARD_SIZE), Err(Error::OutOfBounds(0, 1)));
+        assert_eq!(State::Row(0).try_next(), Ok(State::Row(1)));
+        assert_eq!(State::UpDiag(3, 4).try_next(), Err(Error::IllegalState));
+    }
+
+    #[test]
+    fn test_try_get_and_set_bounds() {
+        let mut b = Board::empty();
+        let last = BOARD_SIZE - 1;
+        assert_eq!(b.try_set(last, last, Piece::White), Ok(Piece::Empty));
+        assert_eq!(b.try_get(last, last), Ok(Piece::White));
+        assert_eq!(b.try_get(last, BOARD_SIZE), Err(Error::OutOfBounds(last, BOARD_SIZE)));
+        assert_eq!(b.try_set(BOARD_SIZE, last, Piece::Black), Err(Error::OutOfBounds(BOARD_SIZE, last)));
+        // an out of range set changes nothing
+        assert_eq!(b.count(Piece::Black), 0);
+        // occupied cells are overwritten, returning the stone replaced
+        assert_eq!(b.try_set(last, last, Piece::Black), Ok(Piece::White));
+        assert_eq!(b.try_get(last, last), Ok(Piece::Black));
+        assert!(b.verify_hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal state")]
+    fn test_state_transition_off_path() {
+        State::UpDiag(3, 4).next();
+    }
+
+    #[test]
+    fn test_line_by_index() {
+        let mut b = Board::empty();
+        for i in 0..BOARD_SIZE {
+            b.set(i, (i * 7) % BOARD_SIZE, Piece::Black);
+            b.set((i * 5) % BOARD_SIZE, i, Piece::White);
+        }
+        let lines:Vec<Line> = LineIterator::on(&b).collect();
+        assert_eq!(lines.len(), LINE_COUNT);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(b.line(i), *line);
+        }
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut b = Board::empty();
+        assert_eq!(b.lines().count(), 2 * BOARD_SIZE + 2 * (2 * BOARD_SIZE - 1));
+        b.set(2, 3, Piece::Black);
+        for (i, line) in b.lines().enumerate() {
+            assert_eq!(line, b.line(i));
+        }
+        assert_eq!(b.lines().filter(|line| line.summary().black == 1).count(), 4);
+    }
+
+    #[test]
+    fn test_located_lines() {
+        let mut b = Board::empty();
+        for i in 0..WIN_LENGTH {
+            b.set(10 - i, 4 + i, Piece::White);
+        }
+        let mut found = Vec::new();
+        for (cells, line) in b.located_lines() {
+            assert_eq!(cells.len(), line.size());
+            let six = line.as_slice().windows(WIN_LENGTH).position(|w| w.iter().all(|&p| p == Piece::White));
+            if let Some(start) = six {
+                found.push(cells[start..start + WIN_LENGTH].to_vec());
+            }
+        }
+        // along the up diagonal, which runs from its left end
+        let expected:Vec<(usize, usize)> = (0..WIN_LENGTH).map(|i| (10 - i, 4 + i)).collect();
+        assert_eq!(found, vec![expected]);
+    }
+
+    #[test]
+    fn test_line_views() {
+        let mut b = Board::empty();
+        for i in 0..BOARD_SIZE {
+            b.set(i, (i * 7) % BOARD_SIZE, Piece::Black);
+            b.set((i * 5) % BOARD_SIZE, i, Piece::White);
+        }
+        assert_eq!(b.line_views().count(), LINE_COUNT);
+        for (i, view) in b.line_views().enumerate() {
+            let line = b.line(i);
+            assert_eq!(view.size(), line.size());
+            for j in 0..line.size() {
+                assert_eq!(view.get(j), line.get(j));
+            }
+            assert_eq!(view.to_line(), line);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_line_by_index_out_of_range() {
+        Board::empty().line(LINE_COUNT);
+    }
+
+    #[test]
+    fn test_lines_through() {
+        let mut b = Board::empty();
+        b.set(2, 3, Piece::Black);
+        let lines = b.lines_through(2, 3);
+        assert_eq!(lines[0].size(), BOARD_SIZE);
+        assert_eq!(lines[1].size(), BOARD_SIZE);
+        assert_eq!(lines[2].size(), 6);
+        assert_eq!(lines[3].size(), BOARD_SIZE - 1);
+        assert_eq!(lines[0].get(3), Piece::Black);
+        assert_eq!(lines[1].get(2), Piece::Black);
+        assert_eq!(lines[2].to_string(), "[---X--]");
+        assert_eq!(lines[3].get(2), Piece::Black);
+    }
+
+    #[test]
+    fn test_lines_in_region() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 2, CENTER, Piece::White);
+        let lines:Vec<Line> = b.lines_in_region((CENTER + 1, CENTER - 1), (CENTER - 1, CENTER + 1)).collect();
+        // 3 rows, 3 columns and 5 diagonals each way
+        assert_eq!(lines.len(), 16);
+        assert!(lines.iter().all(|line| line.size() <= 3));
+        assert_eq!(lines[1].to_string(), "[-X-]");
+        assert_eq!(lines.iter().map(|line| line.to_string()).filter(|s| s.contains('X')).count(), 4);
+        assert!(lines.iter().all(|line| !line.to_string().contains('O')));
+
+        let corner:Vec<Line> = b.lines_in_region((0, 0), (0, 0)).collect();
+        assert_eq!(corner.len(), 4);
+        assert_eq!(b.lines_in_region((BOARD_SIZE - 1, 0), (0, BOARD_SIZE - 1)).count(), LINE_COUNT);
+    }
+
+    #[test]
+    fn test_line_count() {
+        assert_eq!(line_count(BOARD_SIZE), LINE_COUNT);
+        assert_eq!(line_count(13), 76);
+        // a 13x13 board in the lower left corner
+        let small:Vec<Line> = Board::empty().lines_in_region((12, 0), (0, 12)).collect();
+        assert_eq!(small.len(), line_count(13));
+        assert_eq!(small.iter().map(|line| line.size()).max(), Some(13));
+    }
+
+    #[test]
+    fn test_small_board() {
+        let mut b = super::Board::<13>::empty();
+        assert_eq!(b.empty_count(), 13 * 13);
+        assert_eq!(b.get(12, 12), Piece::Empty);
+        assert_eq!(b.try_get(13, 0), Err(Error::OutOfBounds(13, 0)));
+        assert_eq!(b.try_set(0, 13, Piece::Black), Err(Error::OutOfBounds(0, 13)));
+        assert!(b.place(6, 6, Piece::Black).is_ok());
+        b.set(12, 0, Piece::White);
+        assert_eq!(b.count(Piece::Black), 1);
+        assert_eq!(b.empty_count(), 13 * 13 - 2);
+
+        // the same order and lengths as on the standard board
+        let lines:Vec<Line<13>> = b.lines().collect();
+        assert_eq!(lines.len(), line_count(13));
+        assert_eq!(b.orthogonals().count(), 26);
+        assert_eq!(b.diagonals().count(), 2 * 25);
+        assert!(lines[..26].iter().all(|line| line.size() == 13));
+        assert_eq!(lines.iter().map(|line| line.size()).max(), Some(13));
+        assert_eq!(lines[26].size(), 1);
+        assert_eq!(lines[6].get(6), Piece::Black);
+        assert_eq!(lines[13].get(12), Piece::White);
+        assert_eq!(b.line_views().count(), line_count(13));
+        assert_eq!(b.try_line(line_count(13)), Err(Error::LineIndex(line_count(13))));
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(&b.line(i), line);
+        }
+        assert_eq!(Line::try_on(&b, 0, 0, 1, 1, 14), Err(Error::LineLength(14)));
+        assert_eq!(Line::try_on(&b, 0, 0, 1, 0, 13).unwrap().get(12), Piece::White);
+
+        assert_eq!(super::State::<13>::Row(12).next(), super::State::Col(0));
+        assert_eq!(super::State::<13>::UpDiag(12, 12).next(), super::State::DownDiag(0, 12));
+
+        assert_eq!(Board::empty().lines().count(), LINE_COUNT);
+        assert_eq!(super::Board::<1>::empty().lines().count(), line_count(1));
+    }
+
+    #[test]
+    fn test_region_capacity() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 1, CENTER - 1, Piece::White);
+        b.set(CENTER + 2, CENTER, Piece::White);
+        assert_eq!(b.region_capacity((CENTER + 1, CENTER - 1), (CENTER - 1, CENTER + 1)), 7);
+        assert_eq!(b.region_capacity((CENTER - 1, CENTER + 1), (CENTER + 1, CENTER - 1)), 7);
+        assert_eq!(b.region_capacity((CENTER, CENTER), (CENTER, CENTER)), 0);
+        assert_eq!(b.region_capacity((BOARD_SIZE - 1, 0), (0, BOARD_SIZE - 1)), b.empty_count());
+    }
+
+    #[test]
+    fn test_set_returns_previous() {
+        let mut b = Board::empty();
+        assert_eq!(b.set(CENTER, CENTER, Piece::Black), Piece::Empty);
+        assert_eq!(b.set(CENTER, CENTER, Piece::White), Piece::Black);
+        assert_eq!(b.get(CENTER, CENTER), Piece::White);
+    }
+
+    #[test]
+    fn test_set_if_empty() {
+        let mut b = Board::empty();
+        assert!(b.set_if_empty(CENTER, CENTER, Piece::Black));
+        assert!(!b.set_if_empty(CENTER, CENTER, Piece::White));
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
+        assert!(!b.set_if_empty(BOARD_SIZE, CENTER, Piece::White));
+    }
+
+    #[test]
+    fn test_place() {
+        let mut b = Board::empty();
+        assert_eq!(b.place(CENTER, CENTER, Piece::Black), Ok(()));
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
         assert_eq!(
-            Line::of("---").to_string(), 
-            "[---]");
+            b.place(CENTER, CENTER, Piece::White),
+            Err(PlaceError::Occupied { row: CENTER, col: CENTER, existing: Piece::Black }));
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(b.place(BOARD_SIZE, 0, Piece::White), Err(PlaceError::OutOfBounds(BOARD_SIZE, 0)));
+        assert_eq!(b.place(0, BOARD_SIZE, Piece::White), Err(PlaceError::OutOfBounds(0, BOARD_SIZE)));
+        assert_eq!(b.place(0, 0, Piece::Empty), Err(PlaceError::NoStone(0, 0)));
+        assert_eq!(b.count(Piece::White), 0);
+    }
+
+    #[test]
+    fn test_from_moves() {
+        assert!(Board::from_moves(&[]).unwrap() == Board::empty());
+        let b = Board::from_moves(&[
+            (Piece::Black, CENTER, CENTER),
+            (Piece::White, CENTER, CENTER + 1),
+            (Piece::White, 0, 0),
+            (Piece::Black, CENTER + 1, CENTER + 1),
+        ]).unwrap();
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(b.get(CENTER, CENTER + 1), Piece::White);
+        assert_eq!(b.get(0, 0), Piece::White);
+        assert_eq!(b.get(CENTER + 1, CENTER + 1), Piece::Black);
+        assert_eq!(b.stone_count(), (2, 2));
+
         assert_eq!(
-            Line::of("---XX-----").to_string(), 
-            "[---XX-----]");
+            Board::from_moves(&[(Piece::Black, CENTER, CENTER), (Piece::White, CENTER, CENTER)]).err(),
+            Some(PlaceError::Occupied { row: CENTER, col: CENTER, existing: Piece::Black }));
         assert_eq!(
-            Line::of("---XXO--OOOO--").to_string(), 
-            "[---XXO--OOOO--]");
+            Board::from_moves(&[(Piece::Black, CENTER, BOARD_SIZE)]).err(),
+            Some(PlaceError::OutOfBounds(CENTER, BOARD_SIZE)));
+        assert_eq!(
+            Board::from_moves(&[(Piece::Black, CENTER, CENTER), (Piece::Empty, 0, 0)]).err(),
+            Some(PlaceError::NoStone(0, 0)));
+    }
+
+    #[test]
+    fn test_winning_windows() {
+        let mut b = Board::empty();
+        for r in 0..WIN_LENGTH - 1 {
+            b.set(r, 4, Piece::Black);
+        }
+        assert_eq!(b.winning_windows(Piece::Black).count(), 0);
+
+        b.set(WIN_LENGTH - 1, 4, Piece::Black);
+        let windows:Vec<_> = b.winning_windows(Piece::Black).collect();
+        assert_eq!(windows, vec![[(0, 4), (1, 4), (2, 4), (3, 4), (4, 4), (5, 4)]]);
+        assert_eq!(b.winning_windows(Piece::White).count(), 0);
+
+        b.set(WIN_LENGTH, 4, Piece::Black);
+        let windows:Vec<_> = b.winning_windows(Piece::Black).collect();
+        assert_eq!(windows, vec![
+            [(0, 4), (1, 4), (2, 4), (3, 4), (4, 4), (5, 4)],
+            [(1, 4), (2, 4), (3, 4), (4, 4), (5, 4), (6, 4)],
+        ]);
+    }
+
+    #[test]
+    fn test_overlay() {
+        let mut a = Board::empty();
+        a.set(CENTER, CENTER, Piece::Black);
+        a.set(0, 0, Piece::White);
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(1, 1, Piece::White);
+
+        let merged = a.overlay(&b).unwrap();
+        assert_eq!(merged.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(merged.get(0, 0), Piece::White);
+        assert_eq!(merged.get(1, 1), Piece::White);
+        assert_eq!(merged.empty_count(), BOARD_SIZE * BOARD_SIZE - 3);
+        assert_eq!(b.overlay(&a).unwrap().to_vec(), merged.to_vec());
+
+        b.set(0, 0, Piece::Black);
+        assert_eq!(a.overlay(&b).err(), Some(ConflictError(0, 0)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_from_bitsets() {
+        // the center is bit 180, in the third word
+        let center = CENTER * BOARD_SIZE + CENTER;
+        let black = [1, 0, 1 << (center - 128)];
+        let white = [2];
+        let b = Board::from_bitsets(&black, &white).unwrap();
+        assert_eq!(b.get(0, 0), Piece::Black);
+        assert_eq!(b.get(0, 1), Piece::White);
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(b.count(Piece::Black), 2);
+        assert_eq!(b.count(Piece::White), 1);
+        assert_eq!(b.empty_count(), BOARD_SIZE * BOARD_SIZE - 3);
+
+        assert_eq!(Board::from_bitsets(&[3], &[2]).err(), Some(BitsetError::Overlap(0, 1)));
+        assert_eq!(Board::from_bitsets(&[0, 0, 0, 0, 0, 1 << 41], &[]).err(), Some(BitsetError::OutOfRange(361)));
+    }
+
+    #[test]
+    fn test_dead_cells() {
+        let mut b = Board::empty();
+        assert!(b.dead_cells().is_empty());
+        // WB-BW along each line through the center
+        for &(rstride, cstride) in DIRECTIONS.iter() {
+            for &sign in [-1, 1].iter() {
+                let at = |step:i32| (
+                    (CENTER as i32 + sign * step * rstride) as usize,
+                    (CENTER as i32 + sign * step * cstride) as usize);
+                b.set(at(1).0, at(1).1, Piece::Black);
+                b.set(at(2).0, at(2).1, Piece::White);
+            }
+        }
+        assert_eq!(b.dead_cells(), vec![(CENTER, CENTER)]);
+        b.set(CENTER, CENTER + 2, Piece::Black);
+        assert!(b.dead_cells().is_empty());
+    }
+
+    #[test]
+    fn test_max_potential_run() {
+        let mut b = Board::empty();
+        assert_eq!(b.max_potential_run(Piece::Black), 0);
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.max_potential_run(Piece::Black), WIN_LENGTH);
+        assert_eq!(b.max_potential_run(Piece::White), 0);
+
+        // boxed in at both ends, with the edge below and White above
+        let mut boxed = Board::empty();
+        for c in 1..5 {
+            boxed.set(0, c, Piece::Black);
+        }
+        boxed.set(0, 0, Piece::White);
+        boxed.set(0, 5, Piece::White);
+        for c in 0..6 {
+            boxed.set(1, c, Piece::White);
+        }
+        assert_eq!(boxed.max_potential_run(Piece::Black), 4);
+    }
+
+    #[test]
+    fn test_threat_rank() {
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH - 1 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        b.set(2, 2, Piece::Black);
+        b.set(2, 3, Piece::Black);
+        let six = b.threat_rank(CENTER, WIN_LENGTH - 1, Piece::Black);
+        let three = b.threat_rank(2, 4, Piece::Black);
+        assert!(three > 0);
+        assert!(six > three);
+        assert!(six >= 10_000);
+        assert_eq!(b.threat_rank(CENTER, 0, Piece::Black), 0);
+        assert_eq!(b.threat_rank(0, BOARD_SIZE - 1, Piece::Black), 0);
+
+        b.set(2, 5, Piece::White);
+        assert!(b.threat_rank(2, 4, Piece::Black) < three);
+        // windows traced back from the top row would start above the board
+        assert_eq!(b.threat_rank(BOARD_SIZE - 1, 1, Piece::Black), 0);
+        assert_eq!(Board::empty().threat_rank(BOARD_SIZE - 1, 5, Piece::Black), 0);
+    }
+
+    #[test]
+    fn test_all_wins() {
+        let mut b = Board::empty();
+        for c in 0..WIN_LENGTH {
+            b.set(0, c, Piece::Black);
+        }
+        b.set(0, WIN_LENGTH - 1, Piece::White);
+        assert!(b.all_wins().is_empty());
+
+        for c in 0..=WIN_LENGTH {
+            b.set(CENTER, c, Piece::Black);
+            b.set(c, BOARD_SIZE - 1, Piece::White);
+        }
+        let wins = b.all_wins();
+        assert_eq!(wins.len(), 4);
+        assert_eq!(wins[0], ([(CENTER, 0), (CENTER, 1), (CENTER, 2), (CENTER, 3), (CENTER, 4), (CENTER, 5)], Piece::Black));
+        assert_eq!(wins[1].0[0], (CENTER, 1));
+        assert!(wins[2..].iter().all(|&(_, piece)| piece == Piece::White));
+    }
+}
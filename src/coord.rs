@@ -0,0 +1,145 @@
+use std::fmt;
+
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+
+// column letters, skipping I as on a Go board
+const COLUMNS : &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum CoordError {
+    // not a column letter followed by a row number
+    Syntax,
+    // a well formed coordinate off the board
+    OutOfRange,
+    // a stone was given twice for the same cell
+    Duplicate(usize, usize),
+}
+
+impl fmt::Display for CoordError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CoordError::Syntax => fmt.write_str("expected a column letter and a row number"),
+            CoordError::OutOfRange => write!(fmt, "coordinate off the {0}x{0} board", BOARD_SIZE),
+            CoordError::Duplicate(row, col) =>
+                write!(fmt, "more than one stone at {}", format_coord(row, col)),
+        }
+    }
+}
+
+/**
+ * Parse a coordinate such as K10, the center point: a column letter
+ * from A at the left, skipping I as is usual for Go boards, followed by
+ * a row number from 1 at the bottom. The letter may be lower case.
+ */
+pub fn parse_coord(s:&str) -> Result<(usize, usize), CoordError> {
+    let mut chars = s.chars();
+    let letter = chars.next().ok_or(CoordError::Syntax)?.to_ascii_uppercase();
+    let col = COLUMNS.find(letter).ok_or(CoordError::Syntax)?;
+    let number = chars.as_str();
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(CoordError::Syntax)
+    }
+    let row = number.parse::<usize>().map_err(|_| CoordError::OutOfRange)?;
+    if col >= BOARD_SIZE || !(1..=BOARD_SIZE).contains(&row) {
+        return Err(CoordError::OutOfRange)
+    }
+    Ok((row - 1, col))
+}
+
+// the inverse of parse_coord
+pub fn format_coord(row:usize, col:usize) -> String {
+    assert!(row < BOARD_SIZE);
+    format!("{}{}", column_letter(col), row + 1)
+}
+
+#[deprecated(note = "renamed to parse_coord")]
+pub fn from_algebraic(s:&str) -> Result<(usize, usize), CoordError> {
+    parse_coord(s)
+}
+
+#[deprecated(note = "renamed to format_coord")]
+pub fn to_algebraic(row:usize, col:usize) -> String {
+    format_coord(row, col)
+}
+
+// the letter parse_coord reads as column col
+pub fn column_letter(col:usize) -> char {
+    assert!(col < BOARD_SIZE);
+    COLUMNS.as_bytes()[col] as char
+}
+
+impl Board {
+    /**
+     * A board with a black stone on each of the black coordinates and a
+     * white stone on each of the white ones.
+     */
+    pub fn from_algebraic_lists(black:&[&str], white:&[&str]) -> Result<Board, CoordError> {
+        let mut board = Board::empty();
+        for &(coords, piece) in [(black, Piece::Black), (white, Piece::White)].iter() {
+            for coord in coords {
+                let (row, col) = parse_coord(coord)?;
+                if board.set(row, col, piece) != Piece::Empty {
+                    return Err(CoordError::Duplicate(row, col))
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::format_coord;
+    use super::parse_coord;
+    use super::CoordError;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_algebraic() {
+        assert_eq!(parse_coord("K10"), Ok((CENTER, CENTER)));
+        assert_eq!(parse_coord("a1"), Ok((0, 0)));
+        assert_eq!(format_coord(CENTER, CENTER - 1), "J10");
+        assert_eq!(parse_coord("I5"), Err(CoordError::Syntax));
+        assert_eq!(parse_coord("K"), Err(CoordError::Syntax));
+        assert_eq!(parse_coord("U1"), Err(CoordError::OutOfRange));
+        assert_eq!(parse_coord("A20"), Err(CoordError::OutOfRange));
+    }
+
+    #[test]
+    fn test_parse_and_format_coord() {
+        let last = BOARD_SIZE - 1;
+        for &(s, cell) in [("A1", (0, 0)), ("T1", (0, last)), ("A19", (last, 0)), ("T19", (last, last)), ("K10", (CENTER, CENTER))].iter() {
+            assert_eq!(parse_coord(s), Ok(cell));
+            assert_eq!(format_coord(cell.0, cell.1), s);
+        }
+        for row in 0..BOARD_SIZE {
+            for col in 0..BOARD_SIZE {
+                assert_eq!(parse_coord(&format_coord(row, col)), Ok((row, col)));
+            }
+        }
+        for s in ["", "10", "K", "KK10", "K-1", "K 10", "I10"].iter() {
+            assert_eq!(parse_coord(s), Err(CoordError::Syntax), "{}", s);
+        }
+        for s in ["A0", "A20", "U5", "Z1"].iter() {
+            assert_eq!(parse_coord(s), Err(CoordError::OutOfRange), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_from_algebraic_lists() {
+        let b = Board::from_algebraic_lists(&["K10"], &["J10"]).unwrap();
+        assert_eq!(b.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(b.get(CENTER, CENTER - 1), Piece::White);
+        assert_eq!(b.count(Piece::Empty), Board::empty().count(Piece::Empty) - 2);
+
+        assert_eq!(
+            Board::from_algebraic_lists(&["K10"], &["k10"]).err(),
+            Some(CoordError::Duplicate(CENTER, CENTER)));
+        assert_eq!(Board::from_algebraic_lists(&["K10", "10K"], &[]).err(), Some(CoordError::Syntax));
+    }
+}
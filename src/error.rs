@@ -0,0 +1,35 @@
+use std::fmt;
+
+/**
+ * What went wrong in one of the non-panicking try_ methods. The
+ * panicking versions of those methods report the same errors through
+ * their panic message.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Error {
+    // a cell off the board
+    OutOfBounds(usize, usize),
+    // a line index of line_count(N) or more, LINE_COUNT on a standard board
+    LineIndex(usize),
+    // a line of zero cells, or too long for the board
+    LineLength(usize),
+    // a character other than -, O or X at the given index of a line string
+    BadGlyph(usize, char),
+    // a line traversal state off the path LineIterator follows
+    IllegalState,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OutOfBounds(row, col) => write!(fmt, "({}, {}) out of range", row, col),
+            Error::LineIndex(index) =>
+                write!(fmt, "line {} out of range", index),
+            Error::LineLength(len) =>
+                write!(fmt, "a line of {} cells doesn't fit the board", len),
+            Error::BadGlyph(index, glyph) =>
+                write!(fmt, "Line strings must consist only of -, O and X (not {} at index {})", glyph, index),
+            Error::IllegalState => fmt.write_str("Illegal state"),
+        }
+    }
+}
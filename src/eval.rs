@@ -0,0 +1,419 @@
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use board::chebyshev_distance;
+use board::Board;
+use board::Line;
+use board::Piece;
+use board::BOARD_SIZE;
+use board::CENTER;
+use board::DIRECTIONS;
+use board::LineId;
+use board::LINE_COUNT;
+use board::WIN_LENGTH;
+use turn::Turn;
+
+/**
+ * The score of a won position, from the winner's point of view.
+ */
+pub const WIN_SCORE : i32 = 1_000_000;
+
+// how far from the stones already played weighted_random_move looks
+const CANDIDATE_RADIUS : usize = 2;
+
+// the value of a window holding this many of one color's stones and none
+// of the other's
+const WINDOW_WEIGHTS : [i32; WIN_LENGTH] = [0, 1, 4, 16, 64, 256];
+
+// the tempo of such a window: 2 if one turn's stones complete it, 1 if
+// it needs two turns, and nothing if it is further from a six
+const TEMPO_WEIGHTS : [i32; WIN_LENGTH] = [0, 0, 1, 1, 2, 2];
+
+/**
+ * The influence of an occupied cell, which can't be mistaken for that of
+ * an empty one as those are never negative.
+ */
+pub const OCCUPIED : i32 = -1;
+
+/**
+ * A positional bonus for a stone at row, col, which is greatest at the
+ * center and falls off with distance from it, reaching zero at the edge.
+ */
+pub fn positional_value(row:usize, col:usize) -> i32 {
+    (CENTER - chebyshev_distance((row, col), (CENTER, CENTER))) as i32
+}
+
+/**
+ * The sum over each WIN_LENGTH window in the line which is open to only
+ * one color of that color's window weight, counted positively for piece
+ * and negatively for the opponent.
+ */
+fn line_score(line:&Line, piece:Piece) -> i32 {
+    if line.size() < WIN_LENGTH {
+        return 0
+    }
+    let mut score = 0;
+    for start in 0..line.size() - WIN_LENGTH + 1 {
+        let mut own = 0;
+        let mut other = 0;
+        for i in start..start + WIN_LENGTH {
+            match line.get(i) {
+                Piece::Empty => (),
+                p if p == piece => own += 1,
+                _ => other += 1,
+            }
+        }
+        if other == 0 {
+            score += WINDOW_WEIGHTS[own];
+        } else if own == 0 {
+            score -= WINDOW_WEIGHTS[other];
+        }
+    }
+    score
+}
+
+/**
+ * The pair_synergy of the stones of piece within one line.
+ */
+fn line_synergy(line:&Line, piece:Piece) -> i32 {
+    if line.size() < WIN_LENGTH {
+        return 0
+    }
+    let opponent = piece.opponent();
+    let stones:Vec<usize> = (0..line.size()).filter(|&i| line.get(i) == piece).collect();
+    let mut synergy = 0;
+    for (n, &a) in stones.iter().enumerate() {
+        for &b in stones[n + 1..].iter().take_while(|&&b| b - a < WIN_LENGTH) {
+            // the windows covering both a and b start between these
+            let first = (b + 1).saturating_sub(WIN_LENGTH);
+            let last = a.min(line.size() - WIN_LENGTH);
+            let open = (first..=last).any(|start| {
+                (start..start + WIN_LENGTH).all(|i| line.get(i) != opponent)
+            });
+            if open {
+                synergy += (WIN_LENGTH - (b - a)) as i32;
+            }
+        }
+    }
+    synergy
+}
+
+impl Board {
+    /**
+     * A bonus for each pair of piece's stones which share a WIN_LENGTH
+     * window free of the opponent's stones, so could still be part of the
+     * same six. Closer pairs earn more: WIN_LENGTH - 1 for adjacent
+     * stones, down to 1 for stones at opposite ends of a window.
+     */
+    pub fn pair_synergy(&self, piece:Piece) -> i32 {
+        assert!(piece != Piece::Empty);
+        (0..LINE_COUNT).map(|i| line_synergy(&self.line(i), piece)).sum()
+    }
+
+    /**
+     * A heuristic score of the position from to_move's point of view,
+     * WIN_SCORE or -WIN_SCORE if the game has been won. The score is zero
+     * sum: each color's score is the negation of the other's.
+     */
+    pub fn evaluate(&self, to_move:Piece) -> i32 {
+        assert!(to_move != Piece::Empty);
+        match self.winner() {
+            Some(p) if p == to_move => WIN_SCORE,
+            Some(_) => -WIN_SCORE,
+            None => (0..LINE_COUNT).map(|i| line_score(&self.line(i), to_move)).sum(),
+        }
+    }
+
+    /**
+     * Each line's part of the evaluation from to_move's point of view,
+     * ordered by line. Unless the game has been won, these sum to
+     * evaluate(to_move).
+     */
+    pub fn line_scores(&self, to_move:Piece) -> Vec<(LineId, i32)> {
+        assert!(to_move != Piece::Empty);
+        (0..LINE_COUNT).map(|i| (i, line_score(&self.line(i), to_move))).collect()
+    }
+
+    /**
+     * Who is ahead in the race to six: the sum of TEMPO_WEIGHTS over
+     * to_move's open windows, less the same sum for the opponent. Only
+     * windows one or two turns from completion count, the nearer ones
+     * double.
+     */
+    pub fn tempo(&self, to_move:Piece) -> i32 {
+        assert!(to_move != Piece::Empty);
+        let mut tempo = 0;
+        for window in self.six_windows() {
+            let mut own = 0;
+            let mut other = 0;
+            for &(r, c) in window.iter() {
+                match self.get(r, c) {
+                    Piece::Empty => (),
+                    p if p == to_move => own += 1,
+                    _ => other += 1,
+                }
+            }
+            if other == 0 && own < WIN_LENGTH {
+                tempo += TEMPO_WEIGHTS[own];
+            } else if own == 0 && other < WIN_LENGTH {
+                tempo -= TEMPO_WEIGHTS[other];
+            }
+        }
+        tempo
+    }
+
+    /**
+     * How strongly piece's stones bear on each empty cell, indexed
+     * [row][col]. Each stone reaches WIN_LENGTH - 1 cells along each of
+     * the eight directions from it, adding WIN_LENGTH - 1 to the cells
+     * next to it and one less for each step further, until an opposing
+     * stone gets in the way. Occupied cells are OCCUPIED.
+     */
+    pub fn influence(&self, piece:Piece) -> [[i32; BOARD_SIZE]; BOARD_SIZE] {
+        assert!(piece != Piece::Empty);
+        let mut influence = [[0; BOARD_SIZE]; BOARD_SIZE];
+        for (r, row) in influence.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                if self.get(r, c) != Piece::Empty {
+                    *cell = OCCUPIED;
+                }
+            }
+        }
+        for (row, col, _) in self.stones().filter(|&(_, _, p)| p == piece) {
+            for &(rstride, cstride) in DIRECTIONS.iter() {
+                for &sign in [-1, 1].iter() {
+                    for step in 1..WIN_LENGTH as i32 {
+                        let r = row as i32 + sign * step * rstride;
+                        let c = col as i32 + sign * step * cstride;
+                        if r < 0 || r >= BOARD_SIZE as i32 || c < 0 || c >= BOARD_SIZE as i32 {
+                            break
+                        }
+                        let (r, c) = (r as usize, c as usize);
+                        match self.get(r, c) {
+                            Piece::Empty => influence[r][c] += WIN_LENGTH as i32 - step,
+                            p if p == piece => (),
+                            _ => break,
+                        }
+                    }
+                }
+            }
+        }
+        influence
+    }
+
+    /**
+     * The score from to_move's point of view after to_move plays turn.
+     * The turn is made and then unmade, so the board is left as it was.
+     */
+    pub fn evaluate_turn(&mut self, turn:&Turn, to_move:Piece) -> i32 {
+        let undo = self.make_move(turn, to_move);
+        let score = self.evaluate(to_move);
+        self.unmake_move(undo);
+        score
+    }
+
+    /**
+     * A random turn for to_move among the nearby_turns, each chosen with
+     * probability proportional to exp(score / temperature) where score
+     * is its evaluate_turn. A temperature of 0 or less always picks the
+     * best turn, the first of several equally good ones, and a high one
+     * picks almost uniformly. None if there is no legal turn.
+     */
+    #[cfg(feature = "rand")]
+    pub fn weighted_random_move(&self, to_move:Piece, temperature:f64, rng:&mut impl Rng) -> Option<Turn> {
+        let mut board = *self;
+        let scored:Vec<(Turn, i32)> = self.nearby_turns(to_move, CANDIDATE_RADIUS)
+            .map(|turn| (turn, board.evaluate_turn(&turn, to_move)))
+            .collect();
+        let best = scored.iter().map(|&(_, score)| score).max()?;
+        if temperature <= 0.0 {
+            return scored.iter().find(|&&(_, score)| score == best).map(|&(turn, _)| turn)
+        }
+        // measured from the best, so that the weights can't overflow
+        let weights:Vec<f64> = scored.iter()
+            .map(|&(_, score)| ((score - best) as f64 / temperature).exp())
+            .collect();
+        let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (&(turn, _), &weight) in scored.iter().zip(weights.iter()) {
+            if pick < weight {
+                return Some(turn)
+            }
+            pick -= weight;
+        }
+        // rounding can leave a sliver past the last weight
+        scored.last().map(|&(turn, _)| turn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::line_score;
+    use super::positional_value;
+    use super::OCCUPIED;
+    use super::WIN_SCORE;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+    use board::LINE_COUNT;
+    use board::WIN_LENGTH;
+    use turn::Turn;
+
+    #[test]
+    fn test_positional_value() {
+        let center = positional_value(CENTER, CENTER);
+        let corner = positional_value(0, 0);
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                assert!(positional_value(r, c) <= center);
+                assert!(positional_value(r, c) >= corner);
+            }
+        }
+        assert!(center > positional_value(CENTER+1, CENTER));
+        assert!(positional_value(CENTER+1, CENTER) > corner);
+        assert_eq!(corner, positional_value(BOARD_SIZE-1, BOARD_SIZE-1));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut b = Board::empty();
+        assert_eq!(b.evaluate(Piece::Black), 0);
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(b.evaluate(Piece::Black) > 0);
+        assert_eq!(b.evaluate(Piece::White), -b.evaluate(Piece::Black));
+    }
+
+    #[test]
+    fn test_pair_synergy() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.pair_synergy(Piece::Black), 0);
+
+        let mut adjacent = b;
+        adjacent.set(CENTER, CENTER + 1, Piece::Black);
+        assert_eq!(adjacent.pair_synergy(Piece::Black), 5);
+        assert_eq!(adjacent.pair_synergy(Piece::White), 0);
+
+        let mut distant = b;
+        distant.set(CENTER, CENTER + 5, Piece::Black);
+        assert_eq!(distant.pair_synergy(Piece::Black), 1);
+        distant.set(CENTER, CENTER + 6, Piece::Black);
+        assert_eq!(distant.pair_synergy(Piece::Black), 1 + 5);
+
+        let mut blocked = b;
+        blocked.set(CENTER, CENTER + 2, Piece::Black);
+        blocked.set(CENTER, CENTER + 1, Piece::White);
+        assert_eq!(blocked.pair_synergy(Piece::Black), 0);
+        assert!(adjacent.pair_synergy(Piece::Black) > blocked.pair_synergy(Piece::Black));
+
+        // a pair near the edge still shares a window
+        let mut edge = Board::empty();
+        edge.set(0, 0, Piece::White);
+        edge.set(1, 1, Piece::White);
+        assert_eq!(edge.pair_synergy(Piece::White), 5);
+        edge.set(2, 2, Piece::Black);
+        assert_eq!(edge.pair_synergy(Piece::White), 0);
+    }
+
+    #[test]
+    fn test_line_scores() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER, CENTER + 1, Piece::Black);
+        b.set(2, 3, Piece::White);
+        let scores = b.line_scores(Piece::White);
+        assert_eq!(scores.len(), LINE_COUNT);
+        assert_eq!(scores.iter().map(|&(_, score)| score).sum::<i32>(), b.evaluate(Piece::White));
+        assert_eq!(scores[CENTER], (CENTER, line_score(&b.line(CENTER), Piece::White)));
+        assert!(scores[CENTER].1 < 0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_weighted_random_move() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+        use std::collections::HashSet;
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut b = Board::empty();
+        for c in 0..4 {
+            b.set(CENTER, c, Piece::White);
+        }
+        let win = Turn::pair((CENTER, 4), (CENTER, 5));
+        assert_eq!(b.weighted_random_move(Piece::White, 0.0, &mut rng), Some(win));
+        for _ in 0..10 {
+            assert_eq!(b.weighted_random_move(Piece::White, 1e-6, &mut rng), Some(win));
+        }
+        let varied:HashSet<Turn> = (0..20)
+            .filter_map(|_| b.weighted_random_move(Piece::White, 1e12, &mut rng))
+            .collect();
+        assert!(varied.len() > 10);
+
+        let full = Board::from_vec(&[Piece::Black; BOARD_SIZE * BOARD_SIZE]).unwrap();
+        assert_eq!(full.weighted_random_move(Piece::White, 1.0, &mut rng), None);
+    }
+
+    #[test]
+    fn test_evaluate_turn() {
+        let mut b = Board::empty();
+        for c in 0..4 {
+            b.set(CENTER, c, Piece::White);
+        }
+        let before = b;
+        let win = Turn::pair((CENTER, 4), (CENTER, 5));
+        assert_eq!(b.evaluate_turn(&win, Piece::White), WIN_SCORE);
+        assert!(b.evaluate_turn(&win, Piece::Black) > b.evaluate(Piece::Black));
+        assert!(b == before);
+        assert!(b.verify_hash());
+
+        let mut after = Board::empty();
+        after.set(CENTER + 1, CENTER, Piece::Black);
+        let opening = Turn::single(CENTER + 1, CENTER);
+        let mut empty = Board::empty();
+        assert_eq!(empty.evaluate_turn(&opening, Piece::Black), after.evaluate(Piece::Black));
+    }
+
+    #[test]
+    fn test_tempo() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        assert_eq!(b.tempo(Piece::Black), 0);
+
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        // three windows of four, and more of two and three
+        assert!(b.tempo(Piece::Black) >= 3 * 2);
+        assert_eq!(b.tempo(Piece::White), -b.tempo(Piece::Black));
+
+        let live = b.tempo(Piece::Black);
+        b.set(CENTER, CENTER + 2, Piece::White);
+        assert!(b.tempo(Piece::Black) < live);
+    }
+
+    #[test]
+    fn test_influence() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let influence = b.influence(Piece::Black);
+        assert_eq!(influence[CENTER][CENTER], OCCUPIED);
+        for step in 1..WIN_LENGTH {
+            let expected = (WIN_LENGTH - step) as i32;
+            assert_eq!(influence[CENTER][CENTER + step], expected);
+            assert_eq!(influence[CENTER - step][CENTER], expected);
+            assert_eq!(influence[CENTER + step][CENTER - step], expected);
+            assert_eq!(influence[CENTER - step][CENTER - step], expected);
+        }
+        assert_eq!(influence[CENTER][CENTER + WIN_LENGTH], 0);
+        assert_eq!(influence[CENTER + 1][CENTER + 2], 0);
+        assert!(b.influence(Piece::White).iter().flatten().all(|&i| i <= 0));
+
+        // an opposing stone cuts the influence off
+        b.set(CENTER, CENTER + 2, Piece::White);
+        let influence = b.influence(Piece::Black);
+        assert_eq!(influence[CENTER][CENTER + 1], WIN_LENGTH as i32 - 1);
+        assert_eq!(influence[CENTER][CENTER + 3], 0);
+    }
+}
@@ -0,0 +1,202 @@
+//! A C interface to the board, enabled by the `ffi` feature. Build a
+//! shared library with `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! Boards are opaque `C6Board *` handles created by `c6_board_new` and
+//! released with `c6_board_free`. Pieces are passed as integers: 0 for
+//! an empty cell, 1 for black and 2 for white. Rows and columns count
+//! from 0 at the lower left corner. Every function which can fail
+//! returns a negative `C6_*` error code instead of panicking:
+//!
+//! ```c
+//! typedef struct C6Board C6Board;
+//! C6Board *c6_board_new(void);
+//! void c6_board_free(C6Board *board);
+//! int c6_set(C6Board *board, size_t row, size_t col, int piece);
+//! int c6_get(const C6Board *board, size_t row, size_t col);
+//! int c6_winner(const C6Board *board);
+//! int c6_genmove(const C6Board *board, int piece, size_t *row, size_t *col);
+//! ```
+
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use search::greedy_move;
+
+pub const C6_OK : i32 = 0;
+// a null board or output pointer
+pub const C6_NULL : i32 = -1;
+pub const C6_OUT_OF_RANGE : i32 = -2;
+// a piece code other than 0, 1 or 2, or 0 where a color is required
+pub const C6_BAD_PIECE : i32 = -3;
+// no empty cell remains
+pub const C6_NO_MOVE : i32 = -4;
+
+fn piece_code(piece:Piece) -> i32 {
+    match piece {
+        Piece::Empty => 0,
+        Piece::Black => 1,
+        Piece::White => 2,
+    }
+}
+
+fn piece_of(code:i32) -> Option<Piece> {
+    match code {
+        0 => Some(Piece::Empty),
+        1 => Some(Piece::Black),
+        2 => Some(Piece::White),
+        _ => None,
+    }
+}
+
+/**
+ * Allocate an empty board, to be released with c6_board_free.
+ */
+#[no_mangle]
+pub extern "C" fn c6_board_new() -> *mut Board {
+    Box::into_raw(Box::new(Board::empty()))
+}
+
+/**
+ * # Safety
+ * board must be null or a pointer returned by c6_board_new which has
+ * not already been freed.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn c6_board_free(board:*mut Board) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+/**
+ * Place piece (which may be 0 to clear the cell) at row, col.
+ *
+ * # Safety
+ * board must be null or a live pointer from c6_board_new.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn c6_set(board:*mut Board, row:usize, col:usize, piece:i32) -> i32 {
+    let board = match board.as_mut() {
+        Some(board) => board,
+        None => return C6_NULL,
+    };
+    if row >= BOARD_SIZE || col >= BOARD_SIZE {
+        return C6_OUT_OF_RANGE
+    }
+    match piece_of(piece) {
+        Some(piece) => {
+            board.set(row, col, piece);
+            C6_OK
+        },
+        None => C6_BAD_PIECE,
+    }
+}
+
+/**
+ * The piece code at row, col.
+ *
+ * # Safety
+ * board must be null or a live pointer from c6_board_new.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn c6_get(board:*const Board, row:usize, col:usize) -> i32 {
+    let board = match board.as_ref() {
+        Some(board) => board,
+        None => return C6_NULL,
+    };
+    if row >= BOARD_SIZE || col >= BOARD_SIZE {
+        return C6_OUT_OF_RANGE
+    }
+    piece_code(board.get(row, col))
+}
+
+/**
+ * The piece code of the winner, or 0 if nobody has won.
+ *
+ * # Safety
+ * board must be null or a live pointer from c6_board_new.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn c6_winner(board:*const Board) -> i32 {
+    match board.as_ref() {
+        Some(board) => piece_code(board.winner().unwrap_or(Piece::Empty)),
+        None => C6_NULL,
+    }
+}
+
+/**
+ * Choose a cell for a single stone of piece, storing it in row and col.
+ * The board is not modified; for a two stone turn, place the first
+ * stone and call this again.
+ *
+ * # Safety
+ * board must be null or a live pointer from c6_board_new, and row and
+ * col must each be null or valid for writes.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn c6_genmove(board:*const Board, piece:i32, row:*mut usize, col:*mut usize) -> i32 {
+    let board = match board.as_ref() {
+        Some(board) => board,
+        None => return C6_NULL,
+    };
+    if row.is_null() || col.is_null() {
+        return C6_NULL
+    }
+    let piece = match piece_of(piece) {
+        Some(Piece::Empty) | None => return C6_BAD_PIECE,
+        Some(piece) => piece,
+    };
+    match greedy_move(board, piece) {
+        Some((r, c)) => {
+            *row = r;
+            *col = c;
+            C6_OK
+        },
+        None => C6_NO_MOVE,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ptr;
+    use board::CENTER;
+
+    #[test]
+    fn test_ffi_board() {
+        unsafe {
+            let b = c6_board_new();
+            assert_eq!(c6_get(b, CENTER, CENTER), 0);
+            assert_eq!(c6_set(b, CENTER, CENTER, 1), C6_OK);
+            assert_eq!(c6_get(b, CENTER, CENTER), 1);
+            assert_eq!(c6_set(b, CENTER, CENTER, 3), C6_BAD_PIECE);
+            assert_eq!(c6_set(b, BOARD_SIZE, 0, 1), C6_OUT_OF_RANGE);
+            assert_eq!(c6_get(b, 0, BOARD_SIZE), C6_OUT_OF_RANGE);
+            assert_eq!(c6_winner(b), 0);
+            for c in 0..6 {
+                c6_set(b, 0, c, 2);
+            }
+            assert_eq!(c6_winner(b), 2);
+            c6_board_free(b);
+
+            assert_eq!(c6_get(ptr::null(), 0, 0), C6_NULL);
+            assert_eq!(c6_set(ptr::null_mut(), 0, 0, 1), C6_NULL);
+            assert_eq!(c6_winner(ptr::null()), C6_NULL);
+            c6_board_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_ffi_genmove() {
+        unsafe {
+            let b = c6_board_new();
+            let mut row = 0;
+            let mut col = 0;
+            assert_eq!(c6_genmove(b, 1, &mut row, &mut col), C6_OK);
+            assert_eq!((row, col), (CENTER, CENTER));
+            assert_eq!(c6_genmove(b, 0, &mut row, &mut col), C6_BAD_PIECE);
+            assert_eq!(c6_genmove(b, 1, ptr::null_mut(), &mut col), C6_NULL);
+            c6_board_free(b);
+        }
+    }
+}
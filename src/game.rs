@@ -0,0 +1,767 @@
+use std::fmt;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use board::DIRECTIONS;
+use board::WIN_LENGTH;
+use turn::Move;
+use turn::Turn;
+use turn::Undo;
+use zobrist::Zobrist;
+
+/**
+ * The rule variations a game is played under.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Rules {
+    // Black's first stone must be placed on the center point
+    pub center_opening : bool,
+    // the number of stones in a row which wins
+    pub win_length : usize,
+    // stones placed on every turn after Black's single opening stone
+    pub stones_per_turn : usize,
+    // whether a run longer than win_length also wins
+    pub allow_overline : bool,
+    // the width of the board, which is N for a Game<N>
+    pub board_size : usize,
+}
+
+impl Rules {
+    pub fn connect6() -> Rules {
+        Rules {
+            center_opening: true,
+            win_length: WIN_LENGTH,
+            stones_per_turn: 2,
+            allow_overline: true,
+            board_size: BOARD_SIZE,
+        }
+    }
+
+    // a builder starting from the standard rules
+    pub fn builder() -> RulesBuilder {
+        RulesBuilder { rules: Rules::connect6() }
+    }
+
+    // the center point of the playing area
+    pub fn center(&self) -> (usize, usize) {
+        (self.board_size / 2, self.board_size / 2)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum RulesError {
+    // larger than BOARD_SIZE, or zero
+    BoardSize(usize),
+    // longer than the board is wide, or zero
+    WinLength { win_length: usize, board_size: usize },
+    // a Turn holds one or two stones
+    StonesPerTurn(usize),
+    // rules for one size of board used for a game on another
+    SizeMismatch { board_size: usize, expected: usize },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RulesError::BoardSize(size) =>
+                write!(fmt, "board size {} is not between 1 and {}", size, BOARD_SIZE),
+            RulesError::WinLength { win_length, board_size } =>
+                write!(fmt, "win length {} is not between 1 and the board size {}", win_length, board_size),
+            RulesError::StonesPerTurn(n) => write!(fmt, "{} stones per turn is not 1 or 2", n),
+            RulesError::SizeMismatch { board_size, expected } =>
+                write!(fmt, "rules for a {0}x{0} board can't be played on a {1}x{1} board", board_size, expected),
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum IllegalMove {
+    // the move was for the player who isn't to move
+    WrongColor { expected: Piece, found: Piece },
+    // the move's turn is illegal for the player to move
+    Turn(TurnError),
+}
+
+/**
+ * Chainable setters for a variant of the rules, checked by build().
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct RulesBuilder {
+    rules : Rules,
+}
+
+impl RulesBuilder {
+    pub fn center_opening(mut self, center_opening:bool) -> RulesBuilder {
+        self.rules.center_opening = center_opening;
+        self
+    }
+
+    pub fn win_length(mut self, win_length:usize) -> RulesBuilder {
+        self.rules.win_length = win_length;
+        self
+    }
+
+    pub fn stones_per_turn(mut self, stones_per_turn:usize) -> RulesBuilder {
+        self.rules.stones_per_turn = stones_per_turn;
+        self
+    }
+
+    pub fn allow_overline(mut self, allow_overline:bool) -> RulesBuilder {
+        self.rules.allow_overline = allow_overline;
+        self
+    }
+
+    pub fn board_size(mut self, board_size:usize) -> RulesBuilder {
+        self.rules.board_size = board_size;
+        self
+    }
+
+    pub fn build(self) -> Result<Rules, RulesError> {
+        let rules = self.rules;
+        if rules.board_size < 1 || rules.board_size > BOARD_SIZE {
+            return Err(RulesError::BoardSize(rules.board_size))
+        }
+        if rules.win_length < 1 || rules.win_length > rules.board_size {
+            return Err(RulesError::WinLength { win_length: rules.win_length, board_size: rules.board_size })
+        }
+        if rules.stones_per_turn < 1 || rules.stones_per_turn > 2 {
+            return Err(RulesError::StonesPerTurn(rules.stones_per_turn))
+        }
+        Ok(rules)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TurnError {
+    // one stone is played on the opening turn and two on every other
+    StoneCount { expected: usize, found: usize },
+    OutOfBounds(usize, usize),
+    Occupied(usize, usize),
+    // both stones of the turn were on the same cell
+    SameCell(usize, usize),
+    // the opening stone must be on the center point under these rules
+    OffCenter(usize, usize),
+    // the game has already been won
+    GameOver,
+}
+
+impl fmt::Display for TurnError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TurnError::StoneCount { expected, found } =>
+                write!(fmt, "expected {} stones this turn, found {}", expected, found),
+            TurnError::OutOfBounds(r, c) => write!(fmt, "({}, {}) is off the board", r, c),
+            TurnError::Occupied(r, c) => write!(fmt, "({}, {}) is occupied", r, c),
+            TurnError::SameCell(r, c) => write!(fmt, "both stones are on ({}, {})", r, c),
+            TurnError::OffCenter(r, c) =>
+                write!(fmt, "the opening stone must be on the center point, not ({}, {})", r, c),
+            TurnError::GameOver => fmt.write_str("the game is over"),
+        }
+    }
+}
+
+/**
+ * A game on an N x N board, a standard board unless N is given as in
+ * Game::<13>::new(rules).
+ */
+#[derive(Clone)]
+pub struct Game<const N: usize = BOARD_SIZE> {
+    board : Board<N>,
+    rules : Rules,
+    // number of turns played so far
+    turn : usize,
+    // every turn played, oldest first, so that it can be taken back
+    history : Vec<Undo>,
+    // the zobrist hash of the starting position and after every turn
+    // played or taken back since, oldest first
+    positions : Vec<u64>,
+    // set by the winning turn, after which no more may be played
+    winner : Option<Piece>,
+}
+
+// the game the crate is played on, for spelling out where N isn't inferred
+pub type StandardGame = Game<BOARD_SIZE>;
+
+impl<const N: usize> Game<N> {
+    // a new game, panicking unless the rules are for an N x N board
+    pub fn new(rules:Rules) -> Game<N> {
+        Game::try_new(rules).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(rules:Rules) -> Result<Game<N>, RulesError> {
+        if rules.board_size != N {
+            return Err(RulesError::SizeMismatch { board_size: rules.board_size, expected: N })
+        }
+        let board = Board::empty();
+        Ok(Game { board, rules, turn: 0, history: Vec::new(), positions: vec![board.zobrist()], winner: None })
+    }
+
+    pub fn board(&self) -> &Board<N> {
+        &self.board
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    // the most recently played turn, e.g. to mark it when drawing the board
+    pub fn last_turn(&self) -> Option<&Turn> {
+        self.history.last().map(|undo| undo.turn())
+    }
+
+    // every turn played and not taken back, oldest first
+    pub fn turns(&self) -> Vec<Turn> {
+        self.history.iter().map(|undo| *undo.turn()).collect()
+    }
+
+    // Black plays the opening turn, then the players alternate
+    pub fn to_move(&self) -> Piece {
+        if self.turn.is_multiple_of(2) { Piece::Black } else { Piece::White }
+    }
+
+    /**
+     * The player of the turn after this one. Turns rather than stones
+     * alternate, so Black's single opening stone is followed by White's
+     * two, and so on.
+     */
+    pub fn next_color(&self) -> Piece {
+        if (self.turn + 1).is_multiple_of(2) { Piece::Black } else { Piece::White }
+    }
+
+    /**
+     * Every cell where the player to move may place a stone this turn,
+     * in row-major order.
+     */
+    pub fn legal_moves(&self) -> Vec<(usize, usize)> {
+        if self.turn == 0 && self.rules.center_opening {
+            let (r, c) = self.rules.center();
+            if self.board.get(r, c) == Piece::Empty {
+                return vec![(r, c)]
+            }
+            return Vec::new()
+        }
+        let mut moves = Vec::new();
+        for r in 0..N {
+            for c in 0..N {
+                if self.board.get(r, c) == Piece::Empty {
+                    moves.push((r, c));
+                }
+            }
+        }
+        moves
+    }
+
+    /**
+     * A turn for the player to move chosen uniformly from the legal ones,
+     * or None if the game is over or too few empty cells remain.
+     */
+    #[cfg(feature = "rand")]
+    pub fn random_turn(&self, rng:&mut impl Rng) -> Option<Turn> {
+        if self.winner().is_some() {
+            return None
+        }
+        let mut moves = self.legal_moves();
+        let mut pick = || -> Option<(usize, usize)> {
+            if moves.is_empty() {
+                None
+            } else {
+                Some(moves.swap_remove(rng.gen_range(0..moves.len())))
+            }
+        };
+        let first = pick()?;
+        if self.turn == 0 || self.rules.stones_per_turn == 1 {
+            return Some(Turn::single(first.0, first.1))
+        }
+        let second = pick()?;
+        Some(Turn::pair(first, second))
+    }
+
+    /**
+     * Play a turn for the player to move, returning the player if the
+     * turn won the game. The turn is checked in full before any stone is
+     * placed, so an illegal turn leaves the game unchanged. Only the
+     * lines through the new stones are checked for a win.
+     */
+    pub fn apply_turn(&mut self, turn:&Turn) -> Result<Option<Piece>, TurnError> {
+        self.check_turn(turn)?;
+        let piece = self.to_move();
+        let undo = self.board.make_move(turn, piece);
+        self.history.push(undo);
+        self.positions.push(self.board.zobrist());
+        self.turn += 1;
+        if turn.cells().iter().any(|&(r, c)| self.wins_after(r, c)) {
+            self.winner = Some(piece);
+        }
+        Ok(self.winner)
+    }
+
+    /**
+     * Whether mv could be played now, without playing it: it must be for
+     * the player to move, and its turn must pass check_turn.
+     */
+    pub fn check_move(&self, mv:Move) -> Result<(), IllegalMove> {
+        if mv.piece() != self.to_move() {
+            return Err(IllegalMove::WrongColor { expected: self.to_move(), found: mv.piece() })
+        }
+        self.check_turn(mv.turn()).map_err(IllegalMove::Turn)
+    }
+
+    /**
+     * Play mv as apply_turn plays its turn, after checking that it is
+     * for the player to move. An illegal move leaves the game unchanged.
+     */
+    pub fn apply(&mut self, mv:Move) -> Result<Option<Piece>, IllegalMove> {
+        self.check_move(mv)?;
+        self.apply_turn(mv.turn()).map_err(IllegalMove::Turn)
+    }
+
+    // the checks apply_turn makes before playing turn
+    pub fn check_turn(&self, turn:&Turn) -> Result<(), TurnError> {
+        if self.winner().is_some() {
+            return Err(TurnError::GameOver)
+        }
+        let expected = if self.turn == 0 { 1 } else { self.rules.stones_per_turn };
+        if turn.size() != expected {
+            return Err(TurnError::StoneCount { expected, found: turn.size() })
+        }
+        turn.validate(&self.board)?;
+        let cells = turn.cells();
+        if self.turn == 0 && self.rules.center_opening && cells[0] != self.rules.center() {
+            return Err(TurnError::OffCenter(cells[0].0, cells[0].1))
+        }
+        Ok(())
+    }
+
+    /**
+     * Take back the most recent turn, returning it with the color of the
+     * stones taken back, or None at the start of the game.
+     */
+    pub fn undo(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        self.board.unmake_move(undo);
+        self.positions.push(self.board.zobrist());
+        self.turn -= 1;
+        // the game ended with the winning turn, so this was it
+        self.winner = None;
+        Some(Move::new(self.to_move(), *undo.turn()))
+    }
+
+    #[deprecated(note = "undo now returns the Move")]
+    pub fn undo_move(&mut self) -> Option<Move> {
+        self.undo()
+    }
+
+    /**
+     * The zobrist hash of every position the game has passed through,
+     * oldest first: the empty board, then the board after each turn
+     * played or taken back. Undoing a turn appends the earlier position's
+     * hash again rather than removing the later one, so a repeated hash
+     * shows the game has come back to a position.
+     */
+    pub fn position_history(&self) -> &[u64] {
+        &self.positions
+    }
+
+    /**
+     * The board's zobrist hash with the player to move folded in, the key
+     * for a transposition table: the same stones with the other player
+     * to move is a different position. Only the empty board is played
+     * with a single stone, so the turn count needn't be included. The
+     * board's hash covers its size, so games on different sizes of board
+     * don't share keys.
+     */
+    pub fn position_key(&self) -> u64 {
+        self.board.zobrist() ^ Zobrist::standard().side_key(self.to_move())
+    }
+
+    // the color whose turn won the game, if any
+    pub fn winner(&self) -> Option<Piece> {
+        self.winner
+    }
+
+    /**
+     * Whether the stone at row, col is part of a winning run under these
+     * rules: win_length in a row, or longer if overlines are allowed.
+     */
+    fn wins_after(&self, row:usize, col:usize) -> bool {
+        if self.rules.win_length == WIN_LENGTH && self.rules.allow_overline {
+            return self.board.wins_at(row, col).is_some()
+        }
+        let piece = self.board.get(row, col);
+        let size = N as i32;
+        let is_piece = |r:i32, c:i32| -> bool {
+            r >= 0 && r < size && c >= 0 && c < size && self.board.get(r as usize, c as usize) == piece
+        };
+        let (row, col) = (row as i32, col as i32);
+        DIRECTIONS.iter().any(|&(rstride, cstride)| {
+            let mut run = 1;
+            for &sign in [1, -1].iter() {
+                let mut i = 1;
+                while is_piece(row + sign * i * rstride, col + sign * i * cstride) {
+                    run += 1;
+                    i += 1;
+                }
+            }
+            run == self.rules.win_length || (run > self.rules.win_length && self.rules.allow_overline)
+        })
+    }
+
+    /**
+     * Play each turn in order, stopping at the first illegal one. The
+     * error gives that turn's index; every turn before it is applied.
+     */
+    pub fn apply_turns(&mut self, turns:&[Turn]) -> Result<(), (usize, TurnError)> {
+        for (i, turn) in turns.iter().enumerate() {
+            self.apply_turn(turn).map_err(|e| (i, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StandardGame as Game;
+    use super::Rules;
+    use super::IllegalMove;
+    use super::RulesError;
+    use super::TurnError;
+    use turn::Move;
+    use turn::Turn;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::CENTER;
+    use board::BOARD_SIZE;
+
+    #[test]
+    fn test_opening_legal_moves() {
+        let g = Game::new(Rules::connect6());
+        assert_eq!(g.to_move(), Piece::Black);
+        assert_eq!(g.legal_moves(), vec![(CENTER, CENTER)]);
+    }
+
+    #[test]
+    fn test_next_color() {
+        let turns = [
+            Turn::single(CENTER, CENTER),
+            Turn::pair((0, 0), (0, 1)),
+            Turn::pair((1, 0), (1, 1)),
+            Turn::pair((2, 0), (2, 1)),
+        ];
+        let mut g = Game::new(Rules::connect6());
+        let mut colors = Vec::new();
+        for turn in turns.iter() {
+            colors.push(g.to_move());
+            let next = g.next_color();
+            g.apply_turn(turn).unwrap();
+            assert_eq!(g.to_move(), next);
+        }
+        assert_eq!(colors, vec![Piece::Black, Piece::White, Piece::Black, Piece::White]);
+        assert_eq!(g.next_color(), Piece::White);
+    }
+
+    #[test]
+    fn test_unrestricted_legal_moves() {
+        let g = Game::new(Rules::builder().center_opening(false).build().unwrap());
+        assert_eq!(g.legal_moves().len(), BOARD_SIZE * BOARD_SIZE);
+    }
+
+    #[test]
+    fn test_rules_builder() {
+        assert_eq!(Rules::builder().build(), Ok(Rules::connect6()));
+        let rules = Rules::builder()
+            .board_size(15)
+            .win_length(5)
+            .stones_per_turn(1)
+            .allow_overline(false)
+            .build()
+            .unwrap();
+        assert_eq!(rules.center(), (7, 7));
+        assert_eq!(
+            Rules::builder().board_size(5).build(),
+            Err(RulesError::WinLength { win_length: 6, board_size: 5 }));
+        assert_eq!(Rules::builder().board_size(20).build(), Err(RulesError::BoardSize(20)));
+        assert_eq!(Rules::builder().stones_per_turn(3).build(), Err(RulesError::StonesPerTurn(3)));
+
+        // a 15x15 game of exactly five, one stone at a time
+        assert_eq!(Game::try_new(rules).err(), Some(RulesError::SizeMismatch { board_size: 15, expected: BOARD_SIZE }));
+        let mut g = super::Game::<15>::new(rules);
+        assert_eq!(g.legal_moves().len(), 1);
+        assert_eq!(g.apply_turn(&Turn::single(7, 7)), Ok(None));
+        assert_eq!(g.apply_turn(&Turn::single(15, 0)), Err(TurnError::OutOfBounds(15, 0)));
+        assert_eq!(g.apply_turn(&Turn::single(0, 0)), Ok(None));
+        assert_eq!(g.legal_moves().len(), 15 * 15 - 2);
+        for &c in [1, 2, 4, 5].iter() {
+            g.apply_turn(&Turn::single(7, 7 + c)).unwrap();
+            g.apply_turn(&Turn::single(0, 2 * c)).unwrap();
+        }
+        // six in a row is an overline, which doesn't win
+        assert_eq!(g.apply_turn(&Turn::single(7, 10)), Ok(None));
+        assert_eq!(g.winner(), None);
+        g.apply_turn(&Turn::single(1, 0)).unwrap();
+        assert_eq!(g.winner(), None);
+        for r in 2..5 {
+            g.apply_turn(&Turn::single(14, r)).unwrap();
+            g.apply_turn(&Turn::single(r, 0)).unwrap();
+        }
+        assert_eq!(g.winner(), Some(Piece::White));
+        assert_eq!(g.apply_turn(&Turn::single(14, 14)), Err(TurnError::GameOver));
+    }
+
+    #[test]
+    fn test_small_game() {
+        let rules = Rules::builder().board_size(13).build().unwrap();
+        let mut g = super::Game::<13>::new(rules);
+        assert_eq!(g.legal_moves(), vec![(6, 6)]);
+        g.apply_turns(&[
+            Turn::single(6, 6),
+            Turn::pair((0, 0), (12, 12)),
+            Turn::pair((6, 7), (6, 8)),
+            Turn::pair((0, 1), (12, 11)),
+        ]).unwrap();
+        assert_eq!(g.board().empty_count(), 13 * 13 - 7);
+        assert_eq!(g.board().legal_turns(Piece::White).count(), (13 * 13 - 7) * (13 * 13 - 8) / 2);
+        assert_eq!(g.board().threat_count(Piece::Black), 0);
+        assert_eq!(g.apply_turn(&Turn::pair((6, 9), (13, 0))), Err(TurnError::OutOfBounds(13, 0)));
+        assert_eq!(g.apply_turn(&Turn::pair((6, 9), (6, 10))), Ok(None));
+        assert!(g.board().threat_count(Piece::Black) > 0);
+        g.apply_turn(&Turn::pair((12, 0), (12, 1))).unwrap();
+        assert_eq!(g.apply_turn(&Turn::pair((6, 11), (6, 12))), Ok(Some(Piece::Black)));
+        assert!(g.board().verify_hash());
+    }
+
+    #[test]
+    fn test_apply_turn() {
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.apply_turn(&Turn::single(0, 0)), Err(TurnError::OffCenter(0, 0)));
+        assert_eq!(
+            g.apply_turn(&Turn::pair((CENTER, CENTER), (0, 0))),
+            Err(TurnError::StoneCount { expected: 1, found: 2 }));
+        assert_eq!(g.apply_turn(&Turn::single(CENTER, CENTER)), Ok(None));
+        assert_eq!(g.board().get(CENTER, CENTER), Piece::Black);
+        assert_eq!(g.to_move(), Piece::White);
+
+        assert_eq!(
+            g.apply_turn(&Turn::single(0, 0)),
+            Err(TurnError::StoneCount { expected: 2, found: 1 }));
+        assert_eq!(
+            g.apply_turn(&Turn::pair((0, 0), (CENTER, CENTER))),
+            Err(TurnError::Occupied(CENTER, CENTER)));
+        assert_eq!(
+            g.apply_turn(&Turn::pair((0, 0), (0, BOARD_SIZE))),
+            Err(TurnError::OutOfBounds(0, BOARD_SIZE)));
+        assert_eq!(g.apply_turn(&Turn::pair((0, 0), (0, 0))), Err(TurnError::SameCell(0, 0)));
+        assert_eq!(g.board().get(0, 0), Piece::Empty);
+
+        assert_eq!(g.apply_turn(&Turn::pair((0, 0), (0, 1))), Ok(None));
+        assert_eq!(g.board().get(0, 1), Piece::White);
+        assert_eq!(g.to_move(), Piece::Black);
+    }
+
+    #[test]
+    fn test_check_move() {
+        let mut g = Game::new(Rules::connect6());
+        let center = Turn::single(CENTER, CENTER);
+        assert_eq!(
+            g.check_move(Move::new(Piece::White, center)),
+            Err(IllegalMove::WrongColor { expected: Piece::Black, found: Piece::White }));
+        assert_eq!(
+            g.check_move(Move::new(Piece::Black, Turn::single(0, 0))),
+            Err(IllegalMove::Turn(TurnError::OffCenter(0, 0))));
+        assert_eq!(g.check_move(Move::new(Piece::Black, center)), Ok(()));
+        assert_eq!(g.legal_moves().len(), 1);
+
+        g.apply_turn(&center).unwrap();
+        assert_eq!(
+            g.check_move(Move::new(Piece::White, Turn::pair((0, 0), (CENTER, CENTER)))),
+            Err(IllegalMove::Turn(TurnError::Occupied(CENTER, CENTER))));
+        assert_eq!(
+            g.check_move(Move::new(Piece::White, Turn::pair((BOARD_SIZE, 0), (0, 0)))),
+            Err(IllegalMove::Turn(TurnError::OutOfBounds(BOARD_SIZE, 0))));
+        assert_eq!(g.check_move(Move::new(Piece::White, Turn::pair((0, 0), (0, 1)))), Ok(()));
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let moves = [
+            Move::new(Piece::Black, Turn::single(CENTER, CENTER)),
+            Move::new(Piece::White, Turn::pair((0, 0), (0, 1))),
+            Move::new(Piece::Black, Turn::pair((1, 0), (1, 1))),
+        ];
+        let mut g = Game::new(Rules::connect6());
+        for &mv in moves.iter() {
+            g.apply(mv).unwrap();
+        }
+        for &mv in moves.iter().rev() {
+            assert_eq!(g.undo(), Some(mv));
+            assert_eq!(g.to_move(), mv.piece());
+        }
+        assert_eq!(g.undo(), None);
+        assert!(*g.board() == Board::empty());
+        assert_eq!(g.turns(), vec![]);
+        assert_eq!(g.legal_moves(), vec![(CENTER, CENTER)]);
+        // the opening is a single stone again
+        assert_eq!(g.apply(moves[0]), Ok(None));
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(
+            g.apply(Move::new(Piece::Black, Turn::pair((CENTER, CENTER), (0, 0)))),
+            Err(IllegalMove::Turn(TurnError::StoneCount { expected: 1, found: 2 })));
+        assert_eq!(g.apply(Move::new(Piece::Black, Turn::single(CENTER, CENTER))), Ok(None));
+        assert_eq!(g.to_move(), Piece::White);
+
+        assert_eq!(
+            g.apply(Move::new(Piece::Black, Turn::pair((0, 0), (0, 1)))),
+            Err(IllegalMove::WrongColor { expected: Piece::White, found: Piece::Black }));
+        assert_eq!(
+            g.apply(Move::new(Piece::White, Turn::single(0, 0))),
+            Err(IllegalMove::Turn(TurnError::StoneCount { expected: 2, found: 1 })));
+        assert_eq!(
+            g.apply(Move::new(Piece::White, Turn::pair((0, 0), (0, 0)))),
+            Err(IllegalMove::Turn(TurnError::SameCell(0, 0))));
+        assert_eq!(
+            g.apply(Move::new(Piece::White, Turn::pair((0, 0), (CENTER, CENTER)))),
+            Err(IllegalMove::Turn(TurnError::Occupied(CENTER, CENTER))));
+        assert_eq!(g.board().get(0, 0), Piece::Empty);
+
+        assert_eq!(g.apply(Move::new(Piece::White, Turn::pair((0, 0), (0, 1)))), Ok(None));
+        assert_eq!(g.board().get(0, 1), Piece::White);
+        assert_eq!(g.to_move(), Piece::Black);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_turn() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut g = Game::new(Rules::connect6());
+        for _ in 0..20 {
+            let turn = g.random_turn(&mut rng).unwrap();
+            assert_eq!(g.check_move(Move::new(g.to_move(), turn)), Ok(()));
+            g.apply_turn(&turn).unwrap();
+        }
+        assert_eq!(g.board().count(Piece::Empty), BOARD_SIZE * BOARD_SIZE - 39);
+
+        // no turn is legal once the game has been won
+        let mut g = Game::new(Rules::builder().win_length(1).build().unwrap());
+        g.apply_turn(&Turn::single(CENTER, CENTER)).unwrap();
+        assert_eq!(g.random_turn(&mut rng), None);
+    }
+
+    #[test]
+    fn test_apply_turn_reports_win() {
+        let mut g = Game::new(Rules::connect6());
+        g.apply_turns(&[
+            Turn::single(CENTER, CENTER),
+            Turn::pair((0, 0), (0, 1)),
+            Turn::pair((CENTER, 0), (CENTER, 1)),
+            Turn::pair((0, 2), (0, 3)),
+            Turn::pair((CENTER, 2), (CENTER, 3)),
+        ]).unwrap();
+        assert_eq!(g.winner(), None);
+        assert_eq!(g.apply_turn(&Turn::pair((0, 5), (0, 4))), Ok(Some(Piece::White)));
+        assert_eq!(g.winner(), Some(Piece::White));
+        assert_eq!(g.apply_turn(&Turn::pair((CENTER, 4), (CENTER, 5))), Err(TurnError::GameOver));
+        assert!(g.check_turn(&Turn::pair((1, 0), (1, 1))).is_err());
+
+        g.undo();
+        assert_eq!(g.winner(), None);
+        assert_eq!(g.apply_turn(&Turn::pair((1, 0), (1, 1))), Ok(None));
+    }
+
+    #[test]
+    fn test_apply_turns() {
+        let game = [
+            Turn::single(CENTER, CENTER),
+            Turn::pair((0, 0), (0, 1)),
+            Turn::pair((CENTER, CENTER+1), (CENTER, CENTER+2)),
+            Turn::pair((0, 2), (0, 3)),
+            Turn::pair((CENTER, CENTER+3), (CENTER, CENTER+4)),
+            Turn::pair((0, 4), (0, 5)),
+        ];
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.apply_turns(&game), Ok(()));
+        assert_eq!(g.board().winner(), Some(Piece::White));
+        assert_eq!(g.apply_turns(&[Turn::pair((1, 0), (1, 1))]), Err((0, TurnError::GameOver)));
+
+        let mut illegal = game.to_vec();
+        illegal[3] = Turn::pair((0, 2), (CENTER, CENTER));
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.apply_turns(&illegal), Err((3, TurnError::Occupied(CENTER, CENTER))));
+        assert_eq!(g.board().get(CENTER, CENTER+2), Piece::Black);
+        assert_eq!(g.board().get(0, 2), Piece::Empty);
+        assert_eq!(g.to_move(), Piece::White);
+    }
+
+    #[test]
+    fn test_last_turn_and_undo() {
+        let opening = Turn::single(CENTER, CENTER);
+        let reply = Turn::pair((0, 0), (0, 1));
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.last_turn(), None);
+        g.apply_turns(&[opening, reply]).unwrap();
+        assert_eq!(g.last_turn(), Some(&reply));
+        assert_eq!(
+            g.board().to_ascii_grid_marked(g.last_turn().unwrap().cells()).lines().last(),
+            Some("o-o-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+"));
+
+        assert_eq!(g.turns(), vec![opening, reply]);
+        assert_eq!(g.undo(), Some(Move::new(Piece::White, reply)));
+        assert_eq!(g.last_turn(), Some(&opening));
+        assert_eq!(g.turns(), vec![opening]);
+        assert_eq!(g.board().get(0, 0), Piece::Empty);
+        assert_eq!(g.to_move(), Piece::White);
+        assert_eq!(g.undo(), Some(Move::new(Piece::Black, opening)));
+        assert_eq!(g.last_turn(), None);
+        assert_eq!(g.undo(), None);
+        assert_eq!(g.legal_moves(), vec![(CENTER, CENTER)]);
+    }
+
+    #[test]
+    fn test_position_history() {
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.position_history(), &[Board::empty().zobrist()]);
+        g.apply_turns(&[Turn::single(CENTER, CENTER), Turn::pair((0, 0), (0, 1))]).unwrap();
+        assert_eq!(g.position_history().len(), 3);
+        assert_eq!(g.position_history()[2], g.board().zobrist());
+
+        g.undo();
+        let history = g.position_history();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[3], history[1]);
+        assert_ne!(history[3], history[2]);
+    }
+
+    #[test]
+    fn test_position_key() {
+        let mut g = Game::new(Rules::connect6());
+        assert_eq!(g.position_key(), Board::empty().zobrist());
+        g.apply_turn(&Turn::single(CENTER, CENTER)).unwrap();
+        assert!(g.position_key() != g.board().zobrist());
+
+        // the same stones with Black to move instead
+        let mut other = g.clone();
+        other.turn += 1;
+        assert_eq!(other.board().zobrist(), g.board().zobrist());
+        assert!(other.position_key() != g.position_key());
+        assert_eq!(other.position_key(), g.board().zobrist());
+
+        // the same turns on a 13x13 board and the standard one
+        let free = Rules::builder().center_opening(false);
+        let mut small = super::Game::<13>::new(free.board_size(13).build().unwrap());
+        let mut standard = Game::new(free.build().unwrap());
+        assert!(small.position_key() != standard.position_key());
+        let turns = [Turn::single(6, 6), Turn::pair((0, 0), (0, 1))];
+        small.apply_turns(&turns).unwrap();
+        standard.apply_turns(&turns).unwrap();
+        assert!(small.position_key() != standard.position_key());
+    }
+}
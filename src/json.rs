@@ -0,0 +1,234 @@
+use std::fmt;
+
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+
+#[derive(PartialEq, Debug)]
+pub enum JsonError {
+    // malformed JSON at the given byte offset
+    Syntax(usize),
+    // a required field was absent
+    Missing(&'static str),
+    // the "size" field did not match BOARD_SIZE
+    Size(usize),
+    // "cells" was not a BOARD_SIZE x BOARD_SIZE array
+    Shape,
+    // a cell value other than 0, 1 or 2
+    BadCell(usize),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonError::Syntax(pos) => write!(fmt, "malformed JSON at offset {}", pos),
+            JsonError::Missing(field) => write!(fmt, "missing \"{}\" field", field),
+            JsonError::Size(size) => write!(fmt, "unsupported board size {}", size),
+            JsonError::Shape => write!(fmt, "cells must be a {0}x{0} array", BOARD_SIZE),
+            JsonError::BadCell(val) => write!(fmt, "bad cell value {}", val),
+        }
+    }
+}
+
+fn code(piece:Piece) -> usize {
+    match piece {
+        Piece::Empty => 0,
+        Piece::Black => 1,
+        Piece::White => 2,
+    }
+}
+
+impl Board {
+    /**
+     * Write the board as a JSON object of the form
+     * {"size":19,"cells":[[...],...]}, where cells[row][col] is 0 for
+     * an empty cell, 1 for black and 2 for white.
+     */
+    pub fn to_json(&self) -> String {
+        let mut s = format!("{{\"size\":{},\"cells\":[", BOARD_SIZE);
+        for r in 0..BOARD_SIZE {
+            if r > 0 {
+                s.push(',');
+            }
+            s.push('[');
+            for c in 0..BOARD_SIZE {
+                if c > 0 {
+                    s.push(',');
+                }
+                s.push_str(&code(self.get(r, c)).to_string());
+            }
+            s.push(']');
+        }
+        s.push_str("]}");
+        s
+    }
+
+    /**
+     * Parse the object written by to_json. Only that exact shape is
+     * accepted, although the fields may appear in either order.
+     */
+    pub fn from_json(s:&str) -> Result<Board, JsonError> {
+        let mut parser = Parser { bytes: s.as_bytes(), pos: 0 };
+        let mut size = None;
+        let mut cells = None;
+
+        parser.expect(b'{')?;
+        loop {
+            let key = parser.string()?;
+            parser.expect(b':')?;
+            match key.as_str() {
+                "size" => size = Some(parser.number()?),
+                "cells" => cells = Some(parser.cells()?),
+                _ => return Err(JsonError::Syntax(parser.pos)),
+            }
+            if !parser.next_is(b',')? {
+                break
+            }
+        }
+        parser.expect(b'}')?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(JsonError::Syntax(parser.pos))
+        }
+
+        let size = size.ok_or(JsonError::Missing("size"))?;
+        let cells = cells.ok_or(JsonError::Missing("cells"))?;
+        if size != BOARD_SIZE {
+            return Err(JsonError::Size(size))
+        }
+        if cells.len() != BOARD_SIZE || cells.iter().any(|row| row.len() != BOARD_SIZE) {
+            return Err(JsonError::Shape)
+        }
+        let mut board = Board::empty();
+        for (r, row) in cells.iter().enumerate() {
+            for (c, &val) in row.iter().enumerate() {
+                let piece = match val {
+                    0 => Piece::Empty,
+                    1 => Piece::Black,
+                    2 => Piece::White,
+                    _ => return Err(JsonError::BadCell(val)),
+                };
+                board.set(r, c, piece);
+            }
+        }
+        Ok(board)
+    }
+}
+
+// just enough of a JSON parser to read to_json's output
+struct Parser<'a> {
+    bytes : &'a [u8],
+    pos : usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() && (self.bytes[self.pos] as char).is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b:u8) -> Result<(), JsonError> {
+        if self.next_is(b)? {
+            Ok(())
+        } else {
+            Err(JsonError::Syntax(self.pos))
+        }
+    }
+
+    // consume b if it is the next non-whitespace byte
+    fn next_is(&mut self, b:u8) -> Result<bool, JsonError> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(&next) if next == b => {
+                self.pos += 1;
+                Ok(true)
+            },
+            Some(_) => Ok(false),
+            None => Err(JsonError::Syntax(self.pos)),
+        }
+    }
+
+    // a string without escapes
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'"' {
+            if self.bytes[self.pos] == b'\\' {
+                return Err(JsonError::Syntax(self.pos))
+            }
+            self.pos += 1;
+        }
+        let s = String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned();
+        self.expect(b'"')?;
+        Ok(s)
+    }
+
+    // a non-negative integer
+    fn number(&mut self) -> Result<usize, JsonError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.bytes[start..self.pos])
+            .parse()
+            .map_err(|_| JsonError::Syntax(start))
+    }
+
+    fn array<T, F>(&mut self, mut element:F) -> Result<Vec<T>, JsonError>
+            where F: FnMut(&mut Parser<'a>) -> Result<T, JsonError> {
+        let mut items = Vec::new();
+        self.expect(b'[')?;
+        if self.next_is(b']')? {
+            return Ok(items)
+        }
+        loop {
+            items.push(element(self)?);
+            if !self.next_is(b',')? {
+                break
+            }
+        }
+        self.expect(b']')?;
+        Ok(items)
+    }
+
+    fn cells(&mut self) -> Result<Vec<Vec<usize>>, JsonError> {
+        self.array(|p| p.array(|p| p.number()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::JsonError;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, BOARD_SIZE-1, Piece::White);
+        let json = b.to_json();
+        assert!(json.starts_with("{\"size\":19,\"cells\":[[0,0,0,"));
+        let parsed = Board::from_json(&json).unwrap();
+        assert_eq!(parsed.to_json(), json);
+        assert_eq!(parsed.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(parsed.get(0, BOARD_SIZE-1), Piece::White);
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let json = Board::empty().to_json();
+        assert_eq!(Board::from_json("").err(), Some(JsonError::Syntax(0)));
+        assert_eq!(Board::from_json(&json[..json.len()-1]).err(), Some(JsonError::Syntax(json.len()-1)));
+        assert_eq!(Board::from_json("{\"size\":19}").err(), Some(JsonError::Missing("cells")));
+        assert_eq!(Board::from_json("{\"size\":13,\"cells\":[]}").err(), Some(JsonError::Size(13)));
+        assert_eq!(Board::from_json("{\"size\":19,\"cells\":[[0]]}").err(), Some(JsonError::Shape));
+        assert_eq!(
+            Board::from_json(&json.replacen("0", "3", 1)).err(),
+            Some(JsonError::BadCell(3)));
+    }
+}
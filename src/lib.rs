@@ -1,10 +1,46 @@
 #![allow(dead_code)]
 
-mod board;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+
+pub mod bitboard;
+pub mod board;
+pub mod coord;
+pub mod error;
+pub mod eval;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+pub mod json;
+pub mod npy;
+pub mod opening;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod record;
+pub mod render;
+pub mod search;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod sgf;
+pub mod shape;
+pub mod symmetry;
+pub mod threat;
+pub mod turn;
+#[cfg(feature = "tuning")]
+pub mod tuning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zobrist;
 
 #[cfg(test)]
 mod test {
-	use super::board::Board;
+	use super::board::StandardBoard as Board;
 	use super::board::Piece;
 
     #[test]
@@ -12,4 +48,4 @@ mod test {
 		let b = Board::empty();
 		assert_eq!(b.get(0, 0), Piece::Empty);
 	}
-}
\ No newline at end of file
+}
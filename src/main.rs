@@ -0,0 +1,169 @@
+//! Play Connect6 against the engine in the terminal: `connect6 [depth]`,
+//! where depth is how many turns ahead the engine looks for a forced win
+//! (2 by default). You play Black. Enter each turn as one or two
+//! coordinates such as K10, the center point, or K11 L12.
+
+extern crate connect6;
+
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::process;
+
+use connect6::board::Piece;
+use connect6::board::BOARD_SIZE;
+use connect6::coord::parse_coord;
+use connect6::coord::format_coord;
+use connect6::game::Game;
+use connect6::game::Rules;
+use connect6::search::choose_turn;
+use connect6::turn::Turn;
+
+const DEFAULT_DEPTH : usize = 2;
+
+fn main() {
+    let depth = match env::args().nth(1) {
+        None => DEFAULT_DEPTH,
+        Some(arg) => match arg.parse() {
+            Ok(depth) => depth,
+            Err(_) => {
+                eprintln!("usage: connect6 [depth]");
+                process::exit(2)
+            },
+        },
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = play(stdin.lock(), stdout.lock(), depth) {
+        eprintln!("{}", e);
+        process::exit(1)
+    }
+}
+
+/**
+ * Play a game with Black's turns read from input, one per line, and
+ * White's chosen by the engine. Returns the winner, or None for a draw
+ * or if the input ends first.
+ */
+fn play<R:BufRead, W:Write>(input:R, mut output:W, depth:usize) -> io::Result<Option<Piece>> {
+    let mut game = Game::new(Rules::connect6());
+    let mut lines = input.lines();
+    loop {
+        draw(&game, &mut output)?;
+        if let Some(winner) = game.winner() {
+            writeln!(output, "{} wins", name(winner))?;
+            return Ok(Some(winner))
+        }
+        let stones = if game.last_turn().is_none() { 1 } else { 2 };
+        if game.board().empty_count() < stones {
+            writeln!(output, "Draw")?;
+            return Ok(None)
+        }
+
+        if game.to_move() == Piece::White {
+            let turn = choose_turn(game.board(), Piece::White, stones, depth)
+                .expect("there are enough empty cells");
+            game.apply_turn(&turn).expect("the engine's turn is legal");
+            writeln!(output, "White plays {}", describe(&turn))?;
+            continue
+        }
+
+        write!(output, "Black to play {}: ", if stones == 1 { "one stone" } else { "two stones" })?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => {
+                writeln!(output)?;
+                return Ok(None)
+            },
+        };
+        match parse_turn(&line) {
+            Ok(turn) => if let Err(e) = game.apply_turn(&turn) {
+                writeln!(output, "Illegal turn {}: {}", line.trim(), e)?;
+            },
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+fn parse_turn(line:&str) -> Result<Turn, String> {
+    let mut cells = Vec::new();
+    for word in line.split_whitespace() {
+        cells.push(parse_coord(word).map_err(|e| format!("Bad coordinate {}: {}", word, e))?);
+    }
+    match cells.len() {
+        1 => Ok(Turn::single(cells[0].0, cells[0].1)),
+        2 => Ok(Turn::pair(cells[0], cells[1])),
+        _ => Err("Enter one or two coordinates, such as K10 or K11 L12".to_string()),
+    }
+}
+
+// the board with row numbers and column letters, marking the last turn
+fn draw<W:Write>(game:&Game, output:&mut W) -> io::Result<()> {
+    let marked = game.last_turn().map_or(&[][..], |turn| turn.cells());
+    let columns:Vec<String> = (0..BOARD_SIZE).map(|c| format_coord(0, c)[..1].to_string()).collect();
+    writeln!(output, "   {}", columns.join(" "))?;
+    for (i, line) in game.board().to_ascii_grid_marked(marked).lines().enumerate() {
+        if i % 2 == 0 {
+            writeln!(output, "{:2} {}", BOARD_SIZE - i / 2, line)?;
+        } else {
+            writeln!(output, "   {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+fn describe(turn:&Turn) -> String {
+    let cells:Vec<String> = turn.cells().iter().map(|&(r, c)| format_coord(r, c)).collect();
+    cells.join(" ")
+}
+
+fn name(piece:Piece) -> &'static str {
+    match piece {
+        Piece::Black => "Black",
+        Piece::White => "White",
+        Piece::Empty => "Nobody",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_turn;
+    use super::play;
+    use connect6::board::Piece;
+    use connect6::board::CENTER;
+    use connect6::turn::Turn;
+
+    #[test]
+    fn test_parse_turn() {
+        assert_eq!(parse_turn("K10"), Ok(Turn::single(CENTER, CENTER)));
+        assert_eq!(parse_turn(" a1  a2 "), Ok(Turn::pair((0, 0), (1, 0))));
+        assert!(parse_turn("").is_err());
+        assert!(parse_turn("K10 K11 K12").is_err());
+        assert!(parse_turn("Z99").is_err());
+    }
+
+    #[test]
+    fn test_play_scripted() {
+        let mut output = Vec::new();
+        let winner = play(&b"A1\nK10\nnonsense\n"[..], &mut output, 1).unwrap();
+        assert_eq!(winner, None);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Illegal turn A1"));
+        assert!(output.contains("Bad coordinate nonsense"));
+        let reply = output.lines().find(|line| line.starts_with("White plays ")).unwrap();
+        let turn = parse_turn(&reply["White plays ".len()..]).unwrap();
+        assert_eq!(turn.size(), 2);
+        assert!(!turn.cells().contains(&(CENTER, CENTER)));
+        assert!(output.starts_with("   A B C D E F G H J K L M N O P Q R S T\n19 +-+"));
+    }
+
+    #[test]
+    fn test_play_to_the_end() {
+        let mut output = Vec::new();
+        let winner = play(&b"K10\nA1 A2\nA3 A4\nA5 A6\nA7 A8\n"[..], &mut output, 1).unwrap();
+        assert_eq!(winner, Some(Piece::White));
+        assert!(String::from_utf8(output).unwrap().ends_with("White wins\n"));
+    }
+}
@@ -0,0 +1,69 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+
+const MAGIC : &[u8] = b"\x93NUMPY";
+
+impl Board {
+    /**
+     * Write the board as a NumPy .npy (version 1.0) file holding a
+     * BOARD_SIZE x BOARD_SIZE int8 array, where array[row][col] is 0 for
+     * an empty cell, 1 for black and 2 for white, as in to_json.
+     */
+    pub fn to_npy(&self) -> Vec<u8> {
+        let mut header = format!(
+            "{{'descr': '|i1', 'fortran_order': False, 'shape': ({}, {}), }}",
+            BOARD_SIZE, BOARD_SIZE);
+        // the header is padded with spaces and ends in a newline, so that the
+        // data starts on a 64 byte boundary
+        let prefix = MAGIC.len() + 2 + 2;
+        while !(prefix + header.len() + 1).is_multiple_of(64) {
+            header.push(' ');
+        }
+        header.push('\n');
+
+        let mut npy = Vec::with_capacity(prefix + header.len() + BOARD_SIZE * BOARD_SIZE);
+        npy.extend_from_slice(MAGIC);
+        npy.extend_from_slice(&[1, 0]);
+        npy.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        npy.extend_from_slice(header.as_bytes());
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                npy.push(match self.get(r, c) {
+                    Piece::Empty => 0,
+                    Piece::Black => 1,
+                    Piece::White => 2,
+                });
+            }
+        }
+        npy
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+
+    #[test]
+    fn test_to_npy() {
+        let mut b = Board::empty();
+        b.set(0, 1, Piece::Black);
+        b.set(1, 0, Piece::White);
+        let npy = b.to_npy();
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = String::from_utf8(npy[10..10 + header_len].to_vec()).unwrap();
+        assert!(header.contains("'shape': (19, 19)"));
+        assert!(header.contains("'descr': '|i1'"));
+        assert!(header.ends_with(" \n"));
+
+        let data = &npy[10 + header_len..];
+        assert_eq!(data.len(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(data[1], 1);
+        assert_eq!(data[BOARD_SIZE], 2);
+        assert_eq!(data.iter().filter(|&&cell| cell != 0).count(), 2);
+    }
+}
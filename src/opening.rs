@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+
+use board::Board;
+use board::Piece;
+use board::CENTER;
+use turn::Turn;
+
+/**
+ * A position in an opening tree, with the turns leading on from it to
+ * positions first reached there.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub struct OpeningNode {
+    // the zobrist hash of the position's canonical form, so that it can
+    // be looked up however the board is turned
+    hash : u64,
+    children : Vec<(Turn, OpeningNode)>,
+}
+
+impl OpeningNode {
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn children(&self) -> &[(Turn, OpeningNode)] {
+        &self.children
+    }
+
+    // the number of positions in the tree, counting this one
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(|(_, child)| child.size()).sum::<usize>()
+    }
+}
+
+/**
+ * The tree of positions within depth turns after Black's opening stone
+ * on the center point, which is the root. Positions with the same
+ * canonical form appear only once at each depth, under the first turn
+ * found to reach them, so a child may be listed in any of its forms.
+ * Every legal turn is considered, so a depth of two or more is big.
+ */
+pub fn opening_tree(depth:usize) -> OpeningNode {
+    let mut board = Board::empty();
+    board.set(CENTER, CENTER, Piece::Black);
+    // the canonical hashes already in the tree at each depth
+    let mut seen = vec![HashSet::new(); depth];
+    grow(&board, Piece::White, depth, &mut seen)
+}
+
+fn grow(board:&Board, to_move:Piece, depth:usize, seen:&mut [HashSet<u64>]) -> OpeningNode {
+    let hash = board.canonical().zobrist();
+    let (level, deeper) = match seen.split_first_mut() {
+        Some(split) if depth > 0 => split,
+        _ => return OpeningNode { hash, children: Vec::new() },
+    };
+    let positions:Vec<(Turn, Board)> = board.distinct_turns(to_move).into_iter()
+        .filter_map(|turn| {
+            let mut after = *board;
+            after.make_move(&turn, to_move);
+            if level.insert(after.canonical().zobrist()) { Some((turn, after)) } else { None }
+        })
+        .collect();
+    let children = positions.into_iter()
+        .map(|(turn, after)| (turn, grow(&after, to_move.opponent(), depth - 1, deeper)))
+        .collect();
+    OpeningNode { hash, children }
+}
+
+#[cfg(test)]
+mod test {
+    use super::opening_tree;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_opening_tree() {
+        let mut opening = Board::empty();
+        opening.set(CENTER, CENTER, Piece::Black);
+        let root = opening_tree(0);
+        assert_eq!(root.hash(), opening.canonical().zobrist());
+        assert!(root.children().is_empty());
+
+        let tree = opening_tree(1);
+        assert_eq!(tree.hash(), root.hash());
+        // the orbits of pairs of cells around the center, by Burnside's
+        // lemma: a half turn fixes one pair in each of its 2-cycles, and
+        // each reflection those on its axis or swapped by it
+        let cells = BOARD_SIZE * BOARD_SIZE - 1;
+        let axis = BOARD_SIZE - 1;
+        let fixed = cells * (cells - 1) / 2 + cells / 2 + 4 * (axis * (axis - 1) / 2 + (cells - axis) / 2);
+        assert_eq!(tree.children().len(), fixed / 8);
+        assert_eq!(tree.size(), 1 + fixed / 8);
+
+        let hashes:HashSet<u64> = tree.children().iter().map(|(_, child)| child.hash()).collect();
+        assert_eq!(hashes.len(), tree.children().len());
+        let (turn, child) = &tree.children()[0];
+        let mut after = opening;
+        after.make_move(turn, Piece::White);
+        assert_eq!(child.hash(), after.canonical().zobrist());
+        assert!(child.children().is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use board::CENTER;
+
+const MAGIC : &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// pixels between neighboring grid lines, and from the outer lines to the
+// edge of the image
+const CELL : usize = 20;
+
+/**
+ * The width and height of the images drawn by to_png.
+ */
+pub const IMAGE_SIZE : usize = CELL * (BOARD_SIZE + 1);
+
+// x, y, width and height in pixels, from the top left of an image
+pub type Rect = (f32, f32, f32, f32);
+
+const STONE_RADIUS : i32 = CELL as i32 * 9 / 20;
+const STAR_RADIUS : i32 = 3;
+// the star points are where these rows and columns cross, as on a Go board
+const STAR_LINES : [usize; 3] = [3, CENTER, BOARD_SIZE - 4];
+
+const BACKGROUND : [u8; 3] = [220, 179, 92];
+const INK : [u8; 3] = [0, 0, 0];
+const BLACK_STONE : [u8; 3] = [16, 16, 16];
+const WHITE_STONE : [u8; 3] = [250, 250, 250];
+
+// the most data a stored (uncompressed) deflate block can hold
+const MAX_BLOCK : usize = 0xffff;
+
+fn crc32(bytes:&[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes:&[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk(png:&mut Vec<u8>, kind:&[u8; 4], data:&[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// a zlib stream holding the (non-empty) data in stored blocks, which any
+// decoder accepts
+fn zlib_stored(data:&[u8]) -> Vec<u8> {
+    assert!(!data.is_empty());
+    let mut zlib = vec![0x78, 0x01];
+    let blocks:Vec<&[u8]> = data.chunks(MAX_BLOCK).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        // the last block is marked final
+        zlib.push(if i + 1 == blocks.len() { 1 } else { 0 });
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+impl Board {
+    /**
+     * Draw the board as an IMAGE_SIZE x IMAGE_SIZE RGB PNG image: the
+     * grid with its star points and the stones, with row 0 at the bottom
+     * as in to_ascii_grid. The image data isn't compressed.
+     */
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(IMAGE_SIZE * (3 * IMAGE_SIZE + 1));
+        for y in 0..IMAGE_SIZE {
+            // each scanline starts with its filter type, here none
+            pixels.push(0);
+            for x in 0..IMAGE_SIZE {
+                pixels.extend_from_slice(&self.pixel(x, y));
+            }
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(IMAGE_SIZE as u32).to_be_bytes());
+        header.extend_from_slice(&(IMAGE_SIZE as u32).to_be_bytes());
+        // 8 bit RGB, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = MAGIC.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /**
+     * Every cell in row-major order with the square of the to_png image
+     * nearer its intersection than any other. The squares tile the grid, so a click
+     * on the image falls in at most one of them.
+     */
+    pub fn cell_rects(&self) -> Vec<((usize, usize), Rect)> {
+        let size = CELL as f32;
+        let mut rects = Vec::with_capacity(BOARD_SIZE * BOARD_SIZE);
+        for row in 0..BOARD_SIZE {
+            for col in 0..BOARD_SIZE {
+                let x = size * (col as f32 + 0.5);
+                let y = size * ((BOARD_SIZE - 1 - row) as f32 + 0.5);
+                rects.push(((row, col), (x, y, size, size)));
+            }
+        }
+        rects
+    }
+
+    // the color at x, y counting from the top left of the image
+    fn pixel(&self, x:usize, y:usize) -> [u8; 3] {
+        // the nearest intersection, and the offset from it
+        let nearest = |p:usize| ((p + CELL / 2) / CELL).clamp(1, BOARD_SIZE) - 1;
+        let (col, line_from_top) = (nearest(x), nearest(y));
+        let row = BOARD_SIZE - 1 - line_from_top;
+        let dx = x as i32 - (CELL * (col + 1)) as i32;
+        let dy = y as i32 - (CELL * (line_from_top + 1)) as i32;
+        let distance = dx * dx + dy * dy;
+
+        match self.get(row, col) {
+            Piece::Black if distance <= STONE_RADIUS * STONE_RADIUS => return BLACK_STONE,
+            Piece::White if distance < (STONE_RADIUS - 1) * (STONE_RADIUS - 1) => return WHITE_STONE,
+            // white stones are outlined to stand out from the board
+            Piece::White if distance <= STONE_RADIUS * STONE_RADIUS => return INK,
+            _ => (),
+        }
+        if STAR_LINES.contains(&row) && STAR_LINES.contains(&col) && distance <= STAR_RADIUS * STAR_RADIUS {
+            return INK
+        }
+        let on_grid = |p:usize| (CELL..=CELL * BOARD_SIZE).contains(&p);
+        if (dx == 0 && on_grid(y)) || (dy == 0 && on_grid(x)) {
+            return INK
+        }
+        BACKGROUND
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::crc32;
+    use super::IMAGE_SIZE;
+    use super::BACKGROUND;
+    use super::BLACK_STONE;
+    use super::CELL;
+    use super::INK;
+    use super::WHITE_STONE;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    // the width, height and filtered scanlines of a PNG written by to_png
+    fn decode(png:&[u8]) -> (usize, usize, Vec<u8>) {
+        let be = |bytes:&[u8]| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let mut pos = 8;
+        let mut size = (0, 0);
+        let mut zlib = Vec::new();
+        while pos < png.len() {
+            let len = be(&png[pos..]);
+            let body = &png[pos + 4..pos + 8 + len];
+            assert_eq!(crc32(body), be(&png[pos + 8 + len..]) as u32);
+            match &body[..4] {
+                b"IHDR" => size = (be(&body[4..]), be(&body[8..])),
+                b"IDAT" => zlib.extend_from_slice(&body[4..]),
+                b"IEND" => assert_eq!(pos + 12, png.len()),
+                kind => panic!("unexpected chunk {:?}", kind),
+            }
+            pos += 12 + len;
+        }
+
+        let mut data = Vec::new();
+        let mut block = 2;
+        loop {
+            let len = u16::from_le_bytes([zlib[block + 1], zlib[block + 2]]) as usize;
+            data.extend_from_slice(&zlib[block + 5..block + 5 + len]);
+            if zlib[block] & 1 == 1 {
+                break
+            }
+            block += 5 + len;
+        }
+        (size.0, size.1, data)
+    }
+
+    #[test]
+    fn test_cell_rects() {
+        let mut b = Board::empty();
+        b.set(2, 3, Piece::Black);
+        let rects = b.cell_rects();
+        assert_eq!(rects.len(), BOARD_SIZE * BOARD_SIZE);
+        for pair in rects.windows(2) {
+            let ((row, col), (x, y, width, height)) = pair[0];
+            let ((next_row, _), (next_x, next_y, _, _)) = pair[1];
+            if col + 1 < BOARD_SIZE {
+                assert_eq!((next_x, next_y), (x + width, y));
+            } else {
+                // the next row up starts back at the left
+                assert_eq!((next_row, next_x, next_y + height), (row + 1, CELL as f32 / 2.0, y));
+            }
+        }
+        let &(_, (x, y, width, height)) = rects.iter().find(|&&(cell, _)| cell == (2, 3)).unwrap();
+        let (cx, cy) = ((x + width / 2.0) as usize, (y + height / 2.0) as usize);
+        assert_eq!(b.pixel(cx, cy), BLACK_STONE);
+    }
+
+    #[test]
+    fn test_to_png() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        let png = b.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let (width, height, data) = decode(&png);
+        assert_eq!((width, height), (IMAGE_SIZE, IMAGE_SIZE));
+        assert_eq!(data.len(), height * (3 * width + 1));
+        let pixel = |x:usize, y:usize| {
+            let i = y * (3 * width + 1) + 1 + 3 * x;
+            [data[i], data[i + 1], data[i + 2]]
+        };
+        assert_eq!(pixel(0, 0), BACKGROUND);
+        assert_eq!(pixel(CELL * (CENTER + 1), CELL * (CENTER + 1)), BLACK_STONE);
+        // row 0 is at the bottom
+        assert_eq!(pixel(CELL, CELL * BOARD_SIZE), WHITE_STONE);
+        assert_eq!(pixel(CELL * BOARD_SIZE, CELL), INK);
+        assert_eq!(pixel(CELL * 2 + CELL / 2, CELL * 2 + CELL / 2), BACKGROUND);
+    }
+}
@@ -0,0 +1,477 @@
+use std::fmt;
+
+use board::Board;
+use game::Game;
+use game::Rules;
+use game::RulesError;
+use game::TurnError;
+use turn::TimedTurn;
+use turn::Turn;
+
+/**
+ * Why from_deltas couldn't decode a move delta stream.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MoveStreamError {
+    // the stream ends partway through a turn
+    Truncated,
+    // a turn claims to place this many stones, rather than 1 or 2
+    TurnSize(u8),
+    // a stone off the rules' board
+    OutOfRange(usize, usize),
+}
+
+impl fmt::Display for MoveStreamError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MoveStreamError::Truncated => fmt.write_str("the move stream ends partway through a turn"),
+            MoveStreamError::TurnSize(n) => write!(fmt, "a turn of {} stones, not 1 or 2", n),
+            MoveStreamError::OutOfRange(row, col) => write!(fmt, "({}, {}) is off the board", row, col),
+        }
+    }
+}
+
+/**
+ * Why Game::load couldn't restore a saved game.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LoadError {
+    // the data ends before the rules do
+    Truncated,
+    // a yes or no rule stored as a byte other than 0 or 1
+    BadFlag(u8),
+    Rules(RulesError),
+    Moves(MoveStreamError),
+    // the index of the first turn which can't be replayed, and why
+    Turn(usize, TurnError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Truncated => fmt.write_str("the saved game ends partway through its rules"),
+            LoadError::BadFlag(b) => write!(fmt, "a rule flag of {}, not 0 or 1", b),
+            LoadError::Rules(e) => write!(fmt, "bad rules: {}", e),
+            LoadError::Moves(e) => write!(fmt, "bad moves: {}", e),
+            LoadError::Turn(i, e) => write!(fmt, "turn {} can't be played: {}", i, e),
+        }
+    }
+}
+
+// the bytes Game::save writes for the rules, ahead of the turns
+const RULES_LEN : usize = 5;
+
+/**
+ * The turns of a game in the order they were played, with the rules it
+ * was played under and when each stone was placed, if known. replay,
+ * position_at, symmetry_collapses and Replay play it out on a 19x19
+ * Board, panicking for rules of another size; a Game<N> can replay those.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub struct GameRecord {
+    rules : Rules,
+    turns : Vec<Turn>,
+    // times[i] is the placement times of turns[i]'s stones
+    times : Vec<[Option<u64>; 2]>,
+}
+
+impl GameRecord {
+    pub fn new(rules:Rules, turns:Vec<Turn>) -> GameRecord {
+        let times = vec![[None, None]; turns.len()];
+        GameRecord { rules, turns, times }
+    }
+
+    pub fn from_timed(rules:Rules, timed:&[TimedTurn]) -> GameRecord {
+        let turns = timed.iter().map(|t| *t.turn()).collect();
+        let times = timed.iter().map(|t| {
+            let placed_at = t.placed_at();
+            [placed_at[0], placed_at.get(1).cloned().unwrap_or(None)]
+        }).collect();
+        GameRecord { rules, turns, times }
+    }
+
+    // the turns with their placement times
+    pub fn timed_turns(&self) -> Vec<TimedTurn> {
+        self.turns.iter().zip(self.times.iter())
+            .map(|(&turn, &placed_at)| TimedTurn::new(turn, placed_at))
+            .collect()
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    /**
+     * The final position, or the index of the first illegal turn and why
+     * it was illegal.
+     */
+    pub fn replay(&self) -> Result<Board, (usize, TurnError)> {
+        let mut game = Game::new(self.rules);
+        game.apply_turns(&self.turns)?;
+        Ok(*game.board())
+    }
+
+    /**
+     * Pack the turns for sending over the network: for each turn, the
+     * number of stones it places followed by the row and column of each
+     * stone, one byte apiece. The rules and times aren't included.
+     */
+    pub fn to_deltas(&self) -> Vec<u8> {
+        let mut deltas = Vec::with_capacity(5 * self.turns.len());
+        for turn in &self.turns {
+            deltas.push(turn.size() as u8);
+            for &(r, c) in turn.cells() {
+                deltas.push(r as u8);
+                deltas.push(c as u8);
+            }
+        }
+        deltas
+    }
+
+    /**
+     * Unpack the turns written by to_deltas into an untimed record under
+     * the given rules. Only the coordinates are checked, not whether the
+     * turns are legal; replay checks those.
+     */
+    pub fn from_deltas(rules:Rules, deltas:&[u8]) -> Result<GameRecord, MoveStreamError> {
+        let mut turns = Vec::new();
+        let mut bytes = deltas.iter().map(|&b| b as usize);
+        while let Some(size) = bytes.next() {
+            if size != 1 && size != 2 {
+                return Err(MoveStreamError::TurnSize(size as u8))
+            }
+            let mut cells = [(0, 0); 2];
+            for cell in cells.iter_mut().take(size) {
+                let row = bytes.next().ok_or(MoveStreamError::Truncated)?;
+                let col = bytes.next().ok_or(MoveStreamError::Truncated)?;
+                if row >= rules.board_size || col >= rules.board_size {
+                    return Err(MoveStreamError::OutOfRange(row, col))
+                }
+                *cell = (row, col);
+            }
+            turns.push(if size == 1 { Turn::single(cells[0].0, cells[0].1) } else { Turn::pair(cells[0], cells[1]) });
+        }
+        Ok(GameRecord::new(rules, turns))
+    }
+
+    /**
+     * The record of just the first ply turns, with their times, or the
+     * whole record if it is no longer than that.
+     */
+    pub fn truncate(&self, ply:usize) -> GameRecord {
+        let ply = ply.min(self.turns.len());
+        GameRecord {
+            rules: self.rules,
+            turns: self.turns[..ply].to_vec(),
+            times: self.times[..ply].to_vec(),
+        }
+    }
+
+    /**
+     * The position after the first ply turns, as for replay of the
+     * truncated record.
+     */
+    pub fn position_at(&self, ply:usize) -> Result<Board, (usize, TurnError)> {
+        self.truncate(ply).replay()
+    }
+
+    /**
+     * How many of the positions after each turn are left unchanged by
+     * some rotation or reflection, so that a search from there need only
+     * look at part of the board. Turns only add stones, so no position
+     * can be a transform of an earlier one; a game which stays symmetric
+     * is instead one where this counts every turn. Stops at the first
+     * illegal turn.
+     */
+    pub fn symmetry_collapses(&self) -> usize {
+        let mut game = Game::new(self.rules);
+        let mut collapses = 0;
+        for turn in &self.turns {
+            if game.apply_turn(turn).is_err() {
+                break
+            }
+            if game.board().symmetries().len() > 1 {
+                collapses += 1;
+            }
+        }
+        collapses
+    }
+}
+
+impl<const N: usize> Game<N> {
+    /**
+     * Pack the game for resuming later: the board size, win length and
+     * stones per turn, then whether the opening must be central and
+     * whether overlines win as 0 or 1, one byte apiece, followed by the
+     * turns played as in GameRecord::to_deltas. Turns which were taken
+     * back aren't saved, so the restored position_history starts afresh.
+     */
+    pub fn save(&self) -> Vec<u8> {
+        let rules = self.rules();
+        let mut bytes = vec![
+            rules.board_size as u8,
+            rules.win_length as u8,
+            rules.stones_per_turn as u8,
+            rules.center_opening as u8,
+            rules.allow_overline as u8,
+        ];
+        bytes.extend(GameRecord::new(*rules, self.turns()).to_deltas());
+        bytes
+    }
+
+    /**
+     * Restore a game written by save, replaying its turns to check them,
+     * or say why the data isn't a valid saved game. The saved board size
+     * must be N for a Game<N>.
+     */
+    pub fn load(bytes:&[u8]) -> Result<Game<N>, LoadError> {
+        if bytes.len() < RULES_LEN {
+            return Err(LoadError::Truncated)
+        }
+        let flag = |b:u8| match b {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(LoadError::BadFlag(b)),
+        };
+        let rules = Rules::builder()
+            .board_size(bytes[0] as usize)
+            .win_length(bytes[1] as usize)
+            .stones_per_turn(bytes[2] as usize)
+            .center_opening(flag(bytes[3])?)
+            .allow_overline(flag(bytes[4])?)
+            .build()
+            .map_err(LoadError::Rules)?;
+        let record = GameRecord::from_deltas(rules, &bytes[RULES_LEN..]).map_err(LoadError::Moves)?;
+        let mut game = Game::try_new(rules).map_err(LoadError::Rules)?;
+        game.apply_turns(record.turns()).map_err(|(i, e)| LoadError::Turn(i, e))?;
+        Ok(game)
+    }
+}
+
+/**
+ * A cursor over the positions of a recorded game, for stepping back and
+ * forth through it. Position i is the board after the first i turns, so
+ * position 0 is the empty board.
+ */
+pub struct Replay {
+    record : GameRecord,
+    // every position of the game, computed up front
+    positions : Vec<Board>,
+    current : usize,
+}
+
+impl Replay {
+    /**
+     * A cursor at the start of the game, or the index of the record's
+     * first illegal turn and why it was illegal.
+     */
+    pub fn new(record:GameRecord) -> Result<Replay, (usize, TurnError)> {
+        let mut game = Game::new(record.rules);
+        let mut positions = vec![*game.board()];
+        for (i, turn) in record.turns.iter().enumerate() {
+            game.apply_turn(turn).map_err(|e| (i, e))?;
+            positions.push(*game.board());
+        }
+        Ok(Replay { record, positions, current: 0 })
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    // the number of turns played to reach the current position
+    pub fn turn_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.positions[self.current]
+    }
+
+    // play the next turn, or return None at the end of the game
+    pub fn step_forward(&mut self) -> Option<&Board> {
+        self.goto(self.current + 1)
+    }
+
+    // take back the last turn, or return None at the start of the game
+    pub fn step_back(&mut self) -> Option<&Board> {
+        let previous = self.current.checked_sub(1)?;
+        self.goto(previous)
+    }
+
+    /**
+     * Move to the position after turn_index turns, or leave the cursor
+     * where it is and return None if the game is shorter than that.
+     */
+    pub fn goto(&mut self, turn_index:usize) -> Option<&Board> {
+        if turn_index >= self.positions.len() {
+            return None
+        }
+        self.current = turn_index;
+        Some(&self.positions[turn_index])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GameRecord;
+    use super::LoadError;
+    use super::MoveStreamError;
+    use super::Replay;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::CENTER;
+    use game::StandardGame as Game;
+    use game::Rules;
+    use game::RulesError;
+    use game::TurnError;
+    use turn::TimedTurn;
+    use turn::Turn;
+
+    fn record() -> GameRecord {
+        GameRecord::new(Rules::connect6(), vec![
+            Turn::single(CENTER, CENTER),
+            Turn::pair((0, 0), (0, 1)),
+            Turn::pair((CENTER, CENTER+1), (CENTER, CENTER+2)),
+        ])
+    }
+
+    #[test]
+    fn test_replay() {
+        let final_board = record().replay().unwrap();
+        assert_eq!(final_board.count(Piece::Black), 3);
+        assert_eq!(final_board.get(0, 1), Piece::White);
+
+        let illegal = GameRecord::new(Rules::connect6(), vec![Turn::single(0, 0)]);
+        assert_eq!(illegal.replay().err(), Some((0, TurnError::OffCenter(0, 0))));
+        assert!(Replay::new(illegal).is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let record = record();
+        let start = record.truncate(0);
+        assert!(start.turns().is_empty());
+        assert_eq!(start.rules(), record.rules());
+
+        let cut = record.truncate(2);
+        assert_eq!(cut.turns(), &record.turns()[..2]);
+        assert_eq!(cut.replay().unwrap().to_ascii_grid(), record.position_at(2).unwrap().to_ascii_grid());
+        assert_eq!(record.position_at(2).unwrap().count(Piece::Black), 1);
+        assert_eq!(record.truncate(10), record);
+        assert_eq!(record.position_at(0).unwrap().count(Piece::Empty), Board::empty().count(Piece::Empty));
+    }
+
+    #[test]
+    fn test_symmetry_collapses() {
+        assert_eq!(GameRecord::new(Rules::connect6(), vec![]).symmetry_collapses(), 0);
+        // every turn keeps a half turn symmetry until the last
+        let mirrored = GameRecord::new(Rules::connect6(), vec![
+            Turn::single(CENTER, CENTER),
+            Turn::pair((CENTER, CENTER - 1), (CENTER, CENTER + 1)),
+            Turn::pair((CENTER - 1, CENTER), (CENTER + 1, CENTER)),
+            Turn::pair((CENTER - 1, CENTER - 1), (CENTER + 1, CENTER + 1)),
+            Turn::pair((0, 0), (0, 1)),
+        ]);
+        assert_eq!(mirrored.symmetry_collapses(), 4);
+        // only the opening stone alone is symmetric
+        assert_eq!(record().symmetry_collapses(), 1);
+        let illegal = GameRecord::new(Rules::connect6(), vec![Turn::single(CENTER, CENTER), Turn::single(0, 0)]);
+        assert_eq!(illegal.symmetry_collapses(), 1);
+    }
+
+    #[test]
+    fn test_deltas() {
+        let record = record();
+        let deltas = record.to_deltas();
+        assert_eq!(&deltas[..5], &[1, 9, 9, 2, 0]);
+        assert_eq!(deltas.len(), 3 + 5 * 2);
+        assert_eq!(GameRecord::from_deltas(Rules::connect6(), &deltas), Ok(record.clone()));
+        assert_eq!(GameRecord::from_deltas(Rules::connect6(), &[]), Ok(GameRecord::new(Rules::connect6(), vec![])));
+
+        assert_eq!(GameRecord::from_deltas(Rules::connect6(), &deltas[..deltas.len() - 1]), Err(MoveStreamError::Truncated));
+        assert_eq!(GameRecord::from_deltas(Rules::connect6(), &[3, 0, 0]), Err(MoveStreamError::TurnSize(3)));
+        assert_eq!(GameRecord::from_deltas(Rules::connect6(), &[1, 19, 0]), Err(MoveStreamError::OutOfRange(19, 0)));
+        let small = Rules::builder().board_size(9).win_length(6).build().unwrap();
+        assert_eq!(GameRecord::from_deltas(small, &[1, 4, 9]), Err(MoveStreamError::OutOfRange(4, 9)));
+    }
+
+    #[test]
+    fn test_timed_record() {
+        let reply = Turn::pair((0, 0), (0, 1));
+        let timed = [
+            TimedTurn::new(Turn::single(CENTER, CENTER), [Some(0), None]),
+            // the second listed stone went down first
+            TimedTurn::new(reply, [Some(2500), Some(1800)]),
+            TimedTurn::untimed(Turn::pair((1, 0), (1, 1))),
+        ];
+        let record = GameRecord::from_timed(Rules::connect6(), &timed);
+        assert_eq!(record.turns()[1], reply);
+        assert_eq!(record.replay().unwrap().get(0, 1), Piece::White);
+
+        let replay = Replay::new(record).unwrap();
+        let turns = replay.record().timed_turns();
+        assert_eq!(turns, timed.to_vec());
+        assert_eq!(turns[1].placement_order(), vec![(0, 1), (0, 0)]);
+        assert_eq!(turns[2].placement_order(), vec![(1, 0), (1, 1)]);
+        assert_eq!(turns[0].placed_at(), &[Some(0)]);
+        assert_eq!(GameRecord::new(Rules::connect6(), vec![reply]).timed_turns()[0], TimedTurn::untimed(reply));
+    }
+
+    #[test]
+    fn test_replay_cursor() {
+        let record = record();
+        let mut replay = Replay::new(record.clone()).unwrap();
+        assert_eq!(replay.board().count(Piece::Empty), Board::empty().count(Piece::Empty));
+        assert!(replay.step_back().is_none());
+
+        let mut last = None;
+        while let Some(board) = replay.step_forward() {
+            last = Some(board.to_ascii_grid());
+        }
+        assert_eq!(last, Some(record.replay().unwrap().to_ascii_grid()));
+        assert_eq!(replay.turn_index(), 3);
+
+        assert_eq!(replay.step_back().map(|b| b.get(CENTER, CENTER+1)), Some(Piece::Empty));
+        assert_eq!(replay.goto(1).map(|b| b.count(Piece::Black)), Some(1));
+        assert!(replay.goto(4).is_none());
+        assert_eq!(replay.turn_index(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let rules = Rules::builder().allow_overline(false).build().unwrap();
+        let mut game = Game::new(rules);
+        game.apply_turns(record().turns()).unwrap();
+        let saved = game.save();
+        let mut loaded = Game::load(&saved).unwrap();
+        assert_eq!(loaded.rules(), game.rules());
+        assert_eq!(loaded.turns(), game.turns());
+        assert_eq!(loaded.to_move(), game.to_move());
+        assert_eq!(loaded.position_key(), game.position_key());
+
+        // both carry on the same way
+        let next = Turn::pair((0, 2), (0, 3));
+        assert_eq!(loaded.apply_turn(&next), game.apply_turn(&next));
+        assert_eq!(loaded.board().zobrist(), game.board().zobrist());
+        assert_eq!(loaded.save(), game.save());
+        assert_eq!(Game::load(&Game::new(rules).save()).unwrap().turns(), vec![]);
+
+        assert_eq!(Game::load(&saved[..3]).err(), Some(LoadError::Truncated));
+        assert_eq!(Game::load(&saved[..saved.len() - 1]).err(), Some(LoadError::Moves(MoveStreamError::Truncated)));
+        let mut corrupt = saved.clone();
+        corrupt[3] = 2;
+        assert_eq!(Game::load(&corrupt).err(), Some(LoadError::BadFlag(2)));
+        corrupt[3] = 1;
+        corrupt[0] = 0;
+        assert_eq!(Game::load(&corrupt).err(), Some(LoadError::Rules(RulesError::BoardSize(0))));
+        // the second turn played again, onto occupied cells
+        let mut replayed = saved.clone();
+        replayed.extend_from_slice(&[2, 0, 0, 0, 1]);
+        assert_eq!(Game::load(&replayed).err(), Some(LoadError::Turn(3, TurnError::Occupied(0, 0))));
+    }
+}
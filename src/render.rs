@@ -0,0 +1,123 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use coord::format_coord;
+
+/**
+ * The characters used to draw each kind of cell in a text rendering.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct RenderStyle {
+    pub empty : char,
+    pub black : char,
+    pub white : char,
+}
+
+impl RenderStyle {
+    // plain ASCII, with stones as in to_ascii_grid
+    pub fn ascii() -> RenderStyle {
+        RenderStyle { empty: '.', black: 'X', white: 'O' }
+    }
+
+    pub fn unicode() -> RenderStyle {
+        RenderStyle { empty: '·', black: '●', white: '○' }
+    }
+
+    pub fn glyph(&self, piece:Piece) -> char {
+        match piece {
+            Piece::Empty => self.empty,
+            Piece::Black => self.black,
+            Piece::White => self.white,
+        }
+    }
+}
+
+impl Board {
+    /**
+     * Draw the rectangle with the given corners, inclusive, as rows of
+     * glyphs with the highest row at the top, labeled with the column
+     * letters and row numbers of parse_coord so that cells can be
+     * found on the full board. As for lines_in_region, the corners may
+     * be given either way round.
+     */
+    pub fn render_region(&self, top_left:(usize, usize), bottom_right:(usize, usize), style:&RenderStyle) -> String {
+        assert!(top_left.0 < BOARD_SIZE && top_left.1 < BOARD_SIZE);
+        assert!(bottom_right.0 < BOARD_SIZE && bottom_right.1 < BOARD_SIZE);
+        let (min_row, max_row) = (top_left.0.min(bottom_right.0), top_left.0.max(bottom_right.0));
+        let (min_col, max_col) = (top_left.1.min(bottom_right.1), top_left.1.max(bottom_right.1));
+        render((min_row, max_row), (min_col, max_col), |r, c| style.glyph(self.get(r, c)))
+    }
+
+    /**
+     * Draw the whole board as render_region does, with each annotation's
+     * character in place of the empty glyph at its cell, e.g. ! on a
+     * winning cell. Annotations on occupied cells are ignored, so that
+     * the stone still shows; where a cell is annotated more than once
+     * the last annotation wins.
+     */
+    pub fn render_annotated(&self, annotations:&[(usize, usize, char)], style:&RenderStyle) -> String {
+        render((0, BOARD_SIZE - 1), (0, BOARD_SIZE - 1), |r, c| {
+            let piece = self.get(r, c);
+            let mark = annotations.iter().rev().find(|&&(row, col, _)| (row, col) == (r, c));
+            match mark {
+                Some(&(_, _, mark)) if piece == Piece::Empty => mark,
+                _ => style.glyph(piece),
+            }
+        })
+    }
+}
+
+// the labeled rows and columns between the given bounds, inclusive, with
+// glyph(row, col) drawn at each cell
+fn render(rows:(usize, usize), cols:(usize, usize), glyph:impl Fn(usize, usize) -> char) -> String {
+    let columns:Vec<String> = (cols.0..=cols.1).map(|c| format_coord(0, c)[..1].to_string()).collect();
+    let mut s = format!("   {}\n", columns.join(" "));
+    for r in (rows.0..=rows.1).rev() {
+        let glyphs:Vec<String> = (cols.0..=cols.1).map(|c| glyph(r, c).to_string()).collect();
+        s.push_str(&format!("{:2} {}\n", r + 1, glyphs.join(" ")));
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::RenderStyle;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_render_region() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 1, CENTER + 1, Piece::White);
+        let region = b.render_region((CENTER + 1, CENTER - 1), (CENTER - 1, CENTER + 1), &RenderStyle::ascii());
+        assert_eq!(region, "   J K L\n11 . . O\n10 . X .\n 9 . . .\n");
+        assert_eq!(b.render_region((CENTER - 1, CENTER + 1), (CENTER + 1, CENTER - 1), &RenderStyle::ascii()), region);
+
+        let corner = b.render_region((0, 0), (1, 4), &RenderStyle::unicode());
+        let lines:Vec<&str> = corner.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "   A B C D E");
+        assert_eq!(lines[2], " 1 · · · · ·");
+        assert!(!corner.contains('●'));
+    }
+
+    #[test]
+    fn test_render_annotated() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let style = RenderStyle::ascii();
+        let marks = [(CENTER, CENTER + 1, '!'), (0, 0, '?'), (CENTER, CENTER, '!')];
+        let drawing = b.render_annotated(&marks, &style);
+        let lines:Vec<&str> = drawing.lines().collect();
+        assert_eq!(lines.len(), BOARD_SIZE + 1);
+        assert_eq!(lines[0], b.render_region((0, 0), (0, BOARD_SIZE - 1), &style).lines().next().unwrap());
+        // row 10 is the tenth line from the bottom
+        assert_eq!(&lines[BOARD_SIZE - CENTER][3 + 2 * (CENTER - 1)..][..7], ". X ! .");
+        assert!(lines[BOARD_SIZE].starts_with(" 1 ? . ."));
+        assert_eq!(drawing.matches('!').count(), 1);
+        assert_eq!(b.render_annotated(&[], &style).matches('.').count(), BOARD_SIZE * BOARD_SIZE - 1);
+    }
+}
@@ -0,0 +1,266 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use board::LINE_COUNT;
+use eval::positional_value;
+use eval::WIN_SCORE;
+use turn::Move;
+use turn::Turn;
+
+// how far from the stones already played search looks for turns
+const SEARCH_RADIUS : usize = 1;
+
+// beyond any score, so that it can be negated safely
+const INFINITY : i32 = WIN_SCORE + 1;
+
+/**
+ * Choose a single stone for piece by looking one move ahead: take a win
+ * if there is one, and otherwise the cell which most improves piece's
+ * distance to a win relative to the opponent's, preferring central
+ * cells on ties. Returns None if the board is full.
+ */
+pub fn greedy_move(board:&Board, piece:Piece) -> Option<(usize, usize)> {
+    assert!(piece != Piece::Empty);
+    // a side with no open windows is as far from winning as can be
+    let distance = |b:&Board, p:Piece| b.min_stones_to_win(p).unwrap_or(7) as i32;
+
+    let mut scratch = *board;
+    let mut best = None;
+    let empty = (0..BOARD_SIZE)
+        .flat_map(|r| (0..BOARD_SIZE).map(move |c| (r, c)))
+        .filter(|&(r, c)| board.get(r, c) == Piece::Empty);
+    for (r, c) in empty {
+        scratch.set(r, c, piece);
+        let score = if scratch.wins_at(r, c).is_some() {
+            i32::MAX
+        } else {
+            (distance(&scratch, piece.opponent()) - distance(&scratch, piece)) * 100
+                + positional_value(r, c)
+        };
+        scratch.set(r, c, Piece::Empty);
+        if best.is_none_or(|(_, s)| score > s) {
+            best = Some(((r, c), score));
+        }
+    }
+    best.map(|(cell, _)| cell)
+}
+
+/**
+ * Choose a turn of the given number of stones for piece: the first turn
+ * of a forced win within depth turns if there is one (two stone turns
+ * only), and otherwise a greedy_move for each stone in turn. Returns None
+ * if there aren't enough empty cells.
+ */
+pub fn choose_turn(board:&Board, piece:Piece, stones:usize, depth:usize) -> Option<Turn> {
+    assert!(stones == 1 || stones == 2);
+    if stones == 2 && depth > 0 {
+        if let Some(line) = board.find_forced_win(piece, depth) {
+            return Some(line[0])
+        }
+    }
+    let first = greedy_move(board, piece)?;
+    if stones == 1 {
+        return Some(Turn::single(first.0, first.1))
+    }
+    let mut scratch = *board;
+    scratch.set(first.0, first.1, piece);
+    let second = greedy_move(&scratch, piece)?;
+    Some(Turn::pair(first, second))
+}
+
+/**
+ * The best turn for to_move and its score from to_move's point of view,
+ * looking depth turns ahead with alpha-beta search. Only turns within a
+ * cell of the stones already played are tried, and positions at the
+ * depth limit are scored by eval from the point of view of the player
+ * to move there; its scores must lie within WIN_SCORE either way. The
+ * turn is None when depth is 0, the game is over or no turn is legal.
+ */
+pub fn search<F:Fn(&Board, Piece) -> i32>(board:&Board, to_move:Piece, depth:usize, eval:F) -> (Option<Turn>, i32) {
+    let (pv, score) = search_with_pv(board, to_move, depth, eval);
+    (pv.first().cloned(), score)
+}
+
+/**
+ * Search as for search, returning the principal variation: the turns
+ * the search expects both players to play, starting with to_move's
+ * best. It is shorter than depth if the game ends first.
+ */
+pub fn search_with_pv<F:Fn(&Board, Piece) -> i32>(board:&Board, to_move:Piece, depth:usize, eval:F) -> (Vec<Turn>, i32) {
+    assert!(to_move != Piece::Empty);
+    if let Some(winner) = board.winner() {
+        return (Vec::new(), if winner == to_move { WIN_SCORE } else { -WIN_SCORE })
+    }
+    let mut scratch = *board;
+    let (mut pv, score) = negamax(&mut scratch, to_move, depth, -INFINITY, INFINITY, &eval);
+    // the variation was built leaf first
+    pv.reverse();
+    (pv, score)
+}
+
+/**
+ * The move search chooses for to_play looking depth turns ahead, with
+ * the positions at the depth limit scored by threat_score. None when
+ * search returns no turn, as at depth 0 or once the game is over.
+ */
+pub fn best_move(board:&Board, to_play:Piece, depth:u32) -> Option<Move> {
+    let (turn, _) = search(board, to_play, depth as usize, threat_score);
+    turn.map(|turn| Move::new(to_play, turn))
+}
+
+/**
+ * A cheap zero sum evaluation for search from to_move's point of view:
+ * each side's solid fives and live fours (see Line::count_threats) and
+ * longest runs, summed over the lines, minus the opponent's.
+ */
+pub fn threat_score(board:&Board, to_move:Piece) -> i32 {
+    assert!(to_move != Piece::Empty);
+    let side = |piece:Piece| -> i32 {
+        (0..LINE_COUNT).map(|i| {
+            let line = board.line(i);
+            let threats = line.count_threats(piece);
+            1000 * threats.fives as i32 + 300 * threats.live_fours as i32 + 10 * line.longest_run(piece) as i32
+        }).sum()
+    };
+    side(to_move) - side(to_move.opponent())
+}
+
+// the game isn't over yet
+fn negamax<F:Fn(&Board, Piece) -> i32>(board:&mut Board, to_move:Piece, depth:usize, mut alpha:i32, beta:i32, eval:&F)
+        -> (Vec<Turn>, i32) {
+    let turns:Vec<Turn> = if depth == 0 { Vec::new() } else { board.nearby_turns(to_move, SEARCH_RADIUS).collect() };
+    if turns.is_empty() {
+        return (Vec::new(), eval(board, to_move))
+    }
+    let mut best = (Vec::new(), -INFINITY);
+    for turn in turns {
+        let undo = board.make_move(&turn, to_move);
+        // only the new stones can have won
+        let (mut line, score) = if turn.cells().iter().any(|&(r, c)| board.wins_at(r, c).is_some()) {
+            (Vec::new(), -WIN_SCORE)
+        } else {
+            negamax(board, to_move.opponent(), depth - 1, -beta, -alpha, eval)
+        };
+        board.unmake_move(undo);
+        if -score > best.1 {
+            line.push(turn);
+            best = (line, -score);
+        }
+        alpha = alpha.max(-score);
+        if alpha >= beta {
+            break
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::best_move;
+    use super::choose_turn;
+    use super::greedy_move;
+    use super::search;
+    use super::search_with_pv;
+    use super::threat_score;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::CENTER;
+    use eval::WIN_SCORE;
+    use turn::Turn;
+
+    #[test]
+    fn test_greedy_move_opening() {
+        assert_eq!(greedy_move(&Board::empty(), Piece::Black), Some((CENTER, CENTER)));
+    }
+
+    #[test]
+    fn test_greedy_move_wins_and_blocks() {
+        let mut b = Board::empty();
+        for c in 2..7 {
+            b.set(4, c, Piece::White);
+        }
+        b.set(4, 1, Piece::Black);
+        assert_eq!(greedy_move(&b, Piece::White), Some((4, 7)));
+        assert_eq!(greedy_move(&b, Piece::Black), Some((4, 7)));
+    }
+
+    #[test]
+    fn test_choose_turn() {
+        assert_eq!(choose_turn(&Board::empty(), Piece::Black, 1, 2), Some(Turn::single(CENTER, CENTER)));
+
+        let mut b = Board::empty();
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::White);
+        }
+        let turn = choose_turn(&b, Piece::White, 2, 1).unwrap();
+        b.make_move(&turn, Piece::White);
+        assert_eq!(b.winner(), Some(Piece::White));
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let turn = choose_turn(&b, Piece::White, 2, 0).unwrap();
+        assert_eq!(turn.size(), 2);
+        assert!(turn.cells().iter().all(|&(r, c)| b.get(r, c) == Piece::Empty));
+        assert!(turn.cells()[0] != turn.cells()[1]);
+    }
+
+    #[test]
+    fn test_search_with_pv() {
+        // White's four along the edge, with Black beside it
+        let mut b = Board::empty();
+        for c in 1..5 {
+            b.set(0, c, Piece::White);
+        }
+        b.set(1, 2, Piece::Black);
+        b.set(1, 3, Piece::Black);
+        let eval = |board:&Board, piece:Piece| board.evaluate(piece);
+        // one stone beyond the four blocks both its windows
+        let (pv, score) = search_with_pv(&b, Piece::Black, 2, eval);
+        assert_eq!(pv.len(), 2);
+        assert!(pv[0].cells().contains(&(0, 5)));
+        assert_eq!(search(&b, Piece::Black, 2, eval), (Some(pv[0]), score));
+        let mut leaf = b;
+        leaf.make_move(&pv[0], Piece::Black);
+        leaf.make_move(&pv[1], Piece::White);
+        assert_eq!(leaf.evaluate(Piece::Black), score);
+
+        // the variation stops at White's win
+        let (pv, score) = search_with_pv(&b, Piece::White, 2, eval);
+        assert_eq!(pv.len(), 1);
+        assert_eq!(score, WIN_SCORE);
+        assert_eq!(search_with_pv(&b, Piece::White, 0, eval), (Vec::new(), b.evaluate(Piece::White)));
+    }
+
+    #[test]
+    fn test_threat_score() {
+        let mut b = Board::empty();
+        assert_eq!(threat_score(&b, Piece::Black), 0);
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::White);
+        }
+        assert!(threat_score(&b, Piece::White) > 0);
+        assert_eq!(threat_score(&b, Piece::Black), -threat_score(&b, Piece::White));
+    }
+
+    #[test]
+    fn test_best_move() {
+        // White's four along the edge, with Black beside it
+        let mut b = Board::empty();
+        for c in 1..5 {
+            b.set(0, c, Piece::White);
+        }
+        b.set(1, 2, Piece::Black);
+        b.set(1, 3, Piece::Black);
+
+        let win = best_move(&b, Piece::White, 1).unwrap();
+        assert_eq!(win.piece(), Piece::White);
+        let mut after = b;
+        after.make_move(win.turn(), Piece::White);
+        assert_eq!(after.winner(), Some(Piece::White));
+
+        // Black has to block
+        let block = best_move(&b, Piece::Black, 2).unwrap();
+        assert!(!b.turn_is_blunder(block.turn(), Piece::Black));
+        assert_eq!(best_move(&b, Piece::Black, 0), None);
+    }
+}
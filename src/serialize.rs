@@ -0,0 +1,105 @@
+use std::fmt;
+
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use board::Board;
+use board::Piece;
+
+const PIECES : [&str; 3] = ["Empty", "Black", "White"];
+
+// each Piece as its name, read back the same way in any format
+impl Serialize for Piece {
+    fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match *self {
+            Piece::Empty => PIECES[0],
+            Piece::Black => PIECES[1],
+            Piece::White => PIECES[2],
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D:Deserializer<'de>>(deserializer:D) -> Result<Piece, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "Empty" => Ok(Piece::Empty),
+            "Black" => Ok(Piece::Black),
+            "White" => Ok(Piece::White),
+            other => Err(de::Error::unknown_variant(other, &PIECES)),
+        }
+    }
+}
+
+/**
+ * A Board is written as the string its Display impl gives, BOARD_SIZE
+ * lines of -, O and X, and read back with FromStr, so that anything else
+ * is rejected whole.
+ */
+impl Serialize for Board {
+    fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D:Deserializer<'de>>(deserializer:D) -> Result<Board, D::Error> {
+        deserializer.deserialize_str(BoardVisitor)
+    }
+}
+
+struct BoardVisitor;
+
+impl<'de> de::Visitor<'de> for BoardVisitor {
+    type Value = Board;
+
+    fn expecting(&self, fmt:&mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a board drawn as rows of -, O and X")
+    }
+
+    fn visit_str<E:de::Error>(self, s:&str) -> Result<Board, E> {
+        s.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::CENTER;
+    use serde_json;
+
+    #[test]
+    fn test_piece() {
+        for &piece in [Piece::Empty, Piece::Black, Piece::White].iter() {
+            let json = serde_json::to_string(&piece).unwrap();
+            assert_eq!(serde_json::from_str::<Piece>(&json).unwrap(), piece);
+        }
+        assert_eq!(serde_json::to_string(&Piece::Black).unwrap(), "\"Black\"");
+        assert!(serde_json::from_str::<Piece>("\"Red\"").is_err());
+    }
+
+    #[test]
+    fn test_board() {
+        let empty = Board::empty();
+        let json = serde_json::to_string(&empty).unwrap();
+        assert!(serde_json::from_str::<Board>(&json).unwrap() == empty);
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 1, Piece::White);
+        let json = serde_json::to_string(&b).unwrap();
+        assert_eq!(json, serde_json::to_string(&b.to_string()).unwrap());
+        assert!(serde_json::from_str::<Board>(&json).unwrap() == b);
+
+        // a row short, a bad glyph, and not a string at all
+        let short = serde_json::to_string(&b.to_string()[20..]).unwrap();
+        assert!(serde_json::from_str::<Board>(&short).is_err());
+        let bad = serde_json::to_string(&b.to_string().replacen('-', "+", 1)).unwrap();
+        assert!(serde_json::from_str::<Board>(&bad).is_err());
+        assert!(serde_json::from_str::<Board>("[0, 1]").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+use std::str::FromStr;
+
+use board::Piece;
+use board::BOARD_SIZE;
+use game::Game;
+use game::Rules;
+use game::RulesError;
+use game::TurnError;
+use turn::Turn;
+
+/**
+ * Why Game::from_sgf couldn't read a game.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum SgfError {
+    // malformed SGF at the given byte offset
+    Syntax(usize),
+    // an SZ value which isn't a single number
+    Size(String),
+    // a rules property, by name, whose value isn't a single number or flag
+    Property(String),
+    Rules(RulesError),
+    // a move node which isn't a single B or W property, by move number
+    BadNode(usize),
+    // a point which isn't two letters on the board
+    BadPoint(String),
+    // the move was for the player who isn't to move
+    WrongColor { turn: usize, expected: Piece },
+    // the index of the first turn which can't be played, and why
+    Turn(usize, TurnError),
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SgfError::Syntax(pos) => write!(fmt, "malformed SGF at offset {}", pos),
+            SgfError::Size(ref size) => write!(fmt, "unsupported board size {}", size),
+            SgfError::Property(ref ident) => write!(fmt, "bad value for property {}", ident),
+            SgfError::Rules(e) => write!(fmt, "bad rules: {}", e),
+            SgfError::BadNode(i) => write!(fmt, "move {} is not a single B or W property", i),
+            SgfError::BadPoint(ref point) => write!(fmt, "bad point [{}]", point),
+            SgfError::WrongColor { turn, expected } =>
+                write!(fmt, "move {} should be {:?}'s", turn, expected),
+            SgfError::Turn(i, e) => write!(fmt, "move {} can't be played: {}", i, e),
+        }
+    }
+}
+
+// a node's properties, each with its values
+type Node = Vec<(String, Vec<String>)>;
+
+impl<const N: usize> Game<N> {
+    /**
+     * Write the game as SGF: a root node giving the board size, then a
+     * node for each turn, a B or W property with a value for each stone,
+     * as in (;FF[4]GM[Connect6]SZ[19];B[jj];W[ij][kj]). Points are the
+     * column then the row as letters from a, with rows counted from the
+     * top as SGF does. Rules differing from the standard ones follow SZ
+     * in the root node, as WL[win_length], SP[stones_per_turn] and the
+     * flags CO[center_opening] and OL[allow_overline], each 0 or 1.
+     */
+    pub fn to_sgf(&self) -> String {
+        let (rules, standard) = (self.rules(), Rules::connect6());
+        let size = rules.board_size;
+        let mut sgf = format!("(;FF[4]GM[Connect6]SZ[{}]", size);
+        if rules.win_length != standard.win_length {
+            sgf.push_str(&format!("WL[{}]", rules.win_length));
+        }
+        if rules.stones_per_turn != standard.stones_per_turn {
+            sgf.push_str(&format!("SP[{}]", rules.stones_per_turn));
+        }
+        if rules.center_opening != standard.center_opening {
+            sgf.push_str(&format!("CO[{}]", rules.center_opening as u8));
+        }
+        if rules.allow_overline != standard.allow_overline {
+            sgf.push_str(&format!("OL[{}]", rules.allow_overline as u8));
+        }
+        for (i, turn) in self.turns().iter().enumerate() {
+            sgf.push_str(if i % 2 == 0 { ";B" } else { ";W" });
+            for &(row, col) in turn.cells() {
+                sgf.push('[');
+                sgf.push(letter(col));
+                sgf.push(letter(size - 1 - row));
+                sgf.push(']');
+            }
+        }
+        sgf.push(')');
+        sgf
+    }
+
+    /**
+     * Read a game written by to_sgf, replaying its turns under the rules
+     * its root node gives, with the standard rules for any it leaves out.
+     * The root node's other properties are ignored, and a missing SZ
+     * means 19 as in SGF. The size must be N for a Game<N>.
+     */
+    pub fn from_sgf(s:&str) -> Result<Game<N>, SgfError> {
+        let mut nodes = parse(s)?.into_iter();
+        let root = nodes.next().ok_or(SgfError::Syntax(0))?;
+        let size = match root.iter().find(|&(ident, _)| ident == "SZ") {
+            Some((_, values)) if values.len() == 1 =>
+                values[0].parse().map_err(|_| SgfError::Size(values[0].clone()))?,
+            Some((_, values)) => return Err(SgfError::Size(values.join(","))),
+            None => BOARD_SIZE,
+        };
+        let mut rules = Rules::builder().board_size(size);
+        if let Some(win_length) = property(&root, "WL")? {
+            rules = rules.win_length(win_length);
+        }
+        if let Some(stones) = property(&root, "SP")? {
+            rules = rules.stones_per_turn(stones);
+        }
+        if let Some(flag) = flag(&root, "CO")? {
+            rules = rules.center_opening(flag);
+        }
+        if let Some(flag) = flag(&root, "OL")? {
+            rules = rules.allow_overline(flag);
+        }
+        let rules = rules.build().map_err(SgfError::Rules)?;
+
+        let mut game = Game::try_new(rules).map_err(SgfError::Rules)?;
+        for (i, node) in nodes.enumerate() {
+            let (color, values) = match node.as_slice() {
+                [(ident, values)] if ident == "B" => (Piece::Black, values),
+                [(ident, values)] if ident == "W" => (Piece::White, values),
+                _ => return Err(SgfError::BadNode(i)),
+            };
+            if color != game.to_move() {
+                return Err(SgfError::WrongColor { turn: i, expected: game.to_move() })
+            }
+            let cells = values.iter().map(|v| point(v, size)).collect::<Result<Vec<_>, _>>()?;
+            let turn = match *cells.as_slice() {
+                [(row, col)] => Turn::single(row, col),
+                [a, b] => Turn::pair(a, b),
+                _ => return Err(SgfError::BadNode(i)),
+            };
+            game.apply_turn(&turn).map_err(|e| SgfError::Turn(i, e))?;
+        }
+        Ok(game)
+    }
+}
+
+// the single value of the root node's property ident, if it has one
+fn property<T:FromStr>(root:&Node, ident:&str) -> Result<Option<T>, SgfError> {
+    match root.iter().find(|&(i, _)| i == ident) {
+        Some((_, values)) if values.len() == 1 =>
+            values[0].parse().map(Some).map_err(|_| SgfError::Property(ident.to_string())),
+        Some(_) => Err(SgfError::Property(ident.to_string())),
+        None => Ok(None),
+    }
+}
+
+// a property holding 0 or 1
+fn flag(root:&Node, ident:&str) -> Result<Option<bool>, SgfError> {
+    match property::<u8>(root, ident)? {
+        Some(flag) if flag > 1 => Err(SgfError::Property(ident.to_string())),
+        flag => Ok(flag.map(|flag| flag == 1)),
+    }
+}
+
+fn letter(i:usize) -> char {
+    (b'a' + i as u8) as char
+}
+
+// the row and column of an SGF point on a size x size board
+fn point(value:&str, size:usize) -> Result<(usize, usize), SgfError> {
+    let bad = || SgfError::BadPoint(value.to_string());
+    let bytes = value.as_bytes();
+    if bytes.len() != 2 || !bytes.iter().all(|b| b.is_ascii_lowercase()) {
+        return Err(bad())
+    }
+    let (col, from_top) = ((bytes[0] - b'a') as usize, (bytes[1] - b'a') as usize);
+    if col >= size || from_top >= size {
+        return Err(bad())
+    }
+    Ok((size - 1 - from_top, col))
+}
+
+// the nodes of a single game tree without variations, such as to_sgf writes
+fn parse(s:&str) -> Result<Vec<Node>, SgfError> {
+    let bytes = s.trim().as_bytes();
+    let start = s.len() - s.trim_start().len();
+    let mut pos = 0;
+    let error = |pos:usize| SgfError::Syntax(start + pos);
+    if bytes.first() != Some(&b'(') {
+        return Err(error(0))
+    }
+    pos += 1;
+    let mut nodes:Vec<Node> = Vec::new();
+    loop {
+        while pos < bytes.len() && (bytes[pos] as char).is_whitespace() {
+            pos += 1;
+        }
+        match bytes.get(pos) {
+            Some(b';') => nodes.push(Vec::new()),
+            Some(b')') if pos + 1 == bytes.len() && !nodes.is_empty() => return Ok(nodes),
+            Some(c) if c.is_ascii_uppercase() && !nodes.is_empty() => {
+                let ident_start = pos;
+                while pos < bytes.len() && bytes[pos].is_ascii_uppercase() {
+                    pos += 1;
+                }
+                let ident = s.trim()[ident_start..pos].to_string();
+                let mut values = Vec::new();
+                while bytes.get(pos) == Some(&b'[') {
+                    let value_start = pos + 1;
+                    let len = bytes[value_start..].iter().position(|&b| b == b']').ok_or_else(|| error(pos))?;
+                    values.push(s.trim()[value_start..value_start + len].to_string());
+                    pos = value_start + len + 1;
+                }
+                if values.is_empty() {
+                    return Err(error(pos))
+                }
+                nodes.last_mut().unwrap().push((ident, values));
+                continue
+            },
+            _ => return Err(error(pos)),
+        }
+        pos += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SgfError;
+    use board::Piece;
+    use board::CENTER;
+    use game::StandardGame as Game;
+    use game::Rules;
+    use game::RulesError;
+    use game::TurnError;
+    use turn::Turn;
+
+    fn game() -> Game {
+        let mut g = Game::new(Rules::connect6());
+        g.apply_turns(&[
+            Turn::single(CENTER, CENTER),
+            Turn::pair((0, 0), (0, 1)),
+            Turn::pair((CENTER, CENTER + 1), (CENTER + 1, CENTER)),
+        ]).unwrap();
+        g
+    }
+
+    #[test]
+    fn test_to_sgf() {
+        assert_eq!(game().to_sgf(), "(;FF[4]GM[Connect6]SZ[19];B[jj];W[as][bs];B[kj][ji])");
+        assert_eq!(Game::new(Rules::connect6()).to_sgf(), "(;FF[4]GM[Connect6]SZ[19])");
+    }
+
+    #[test]
+    fn test_from_sgf() {
+        let g = game();
+        let loaded = Game::from_sgf(&g.to_sgf()).unwrap();
+        assert_eq!(loaded.turns(), g.turns());
+        assert!(*loaded.board() == *g.board());
+        assert_eq!(loaded.to_move(), Piece::White);
+        assert_eq!(loaded.to_sgf(), g.to_sgf());
+
+        assert_eq!(
+            Game::from_sgf(" (;SZ[9]\n;B[ee]) ").err(),
+            Some(SgfError::Rules(RulesError::SizeMismatch { board_size: 9, expected: 19 })));
+        let small = super::Game::<9>::from_sgf(" (;SZ[9]\n;B[ee]) ").unwrap();
+        assert_eq!(small.rules().board_size, 9);
+        assert_eq!(small.turns(), vec![Turn::single(4, 4)]);
+        assert_eq!(Game::from_sgf("(;GM[Connect6])").unwrap().rules().board_size, 19);
+    }
+
+    #[test]
+    fn test_sgf_rules() {
+        let rules = Rules::builder()
+            .board_size(15)
+            .win_length(5)
+            .center_opening(false)
+            .allow_overline(false)
+            .build()
+            .unwrap();
+        let mut g = super::Game::<15>::new(rules);
+        g.apply_turns(&[Turn::single(0, 0), Turn::pair((14, 14), (14, 13)), Turn::pair((1, 1), (2, 2))]).unwrap();
+        let sgf = g.to_sgf();
+        assert_eq!(sgf, "(;FF[4]GM[Connect6]SZ[15]WL[5]CO[0]OL[0];B[ao];W[oa][na];B[bn][cm])");
+        let loaded = super::Game::<15>::from_sgf(&sgf).unwrap();
+        assert_eq!(*loaded.rules(), rules);
+        assert_eq!(loaded.turns(), g.turns());
+        assert!(*loaded.board() == *g.board());
+
+        let rules = Rules::builder().stones_per_turn(1).build().unwrap();
+        let mut g = Game::new(rules);
+        g.apply_turns(&[Turn::single(CENTER, CENTER), Turn::single(0, 0), Turn::single(1, 1)]).unwrap();
+        let loaded = Game::from_sgf(&g.to_sgf()).unwrap();
+        assert_eq!(*loaded.rules(), rules);
+        assert_eq!(loaded.turns(), g.turns());
+    }
+
+    #[test]
+    fn test_from_sgf_errors() {
+        let sgf = game().to_sgf();
+        assert_eq!(Game::from_sgf(&sgf[..sgf.len() - 1]).err(), Some(SgfError::Syntax(sgf.len() - 1)));
+        assert_eq!(Game::from_sgf(&sgf[..sgf.len() - 4]).err(), Some(SgfError::Syntax(sgf.len() - 5)));
+        assert_eq!(Game::from_sgf("garbage").err(), Some(SgfError::Syntax(0)));
+        assert_eq!(Game::from_sgf("()").err(), Some(SgfError::Syntax(1)));
+        assert_eq!(Game::from_sgf("(;SZ[big])").err(), Some(SgfError::Size("big".to_string())));
+        assert!(Game::from_sgf("(;SZ[25])").is_err());
+        assert_eq!(Game::from_sgf("(;WL[five])").err(), Some(SgfError::Property("WL".to_string())));
+        assert_eq!(Game::from_sgf("(;CO[2])").err(), Some(SgfError::Property("CO".to_string())));
+        assert!(Game::from_sgf("(;SZ[9]WL[10])").is_err());
+        assert_eq!(Game::from_sgf("(;SZ[19];W[jj])").err(), Some(SgfError::WrongColor { turn: 0, expected: Piece::Black }));
+        assert_eq!(Game::from_sgf("(;SZ[19];B[jj]C[hi])").err(), Some(SgfError::BadNode(0)));
+        assert_eq!(Game::from_sgf("(;SZ[19];B[zz])").err(), Some(SgfError::BadPoint("zz".to_string())));
+        assert_eq!(
+            Game::from_sgf("(;SZ[19];B[jj];W[jj][aa])").err(),
+            Some(SgfError::Turn(1, TurnError::Occupied(CENTER, CENTER))));
+    }
+}
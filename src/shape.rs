@@ -0,0 +1,117 @@
+use board::Board;
+use board::Line;
+use board::Piece;
+use board::LINE_COUNT;
+use board::WIN_LENGTH;
+
+// one cell of a Shape
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ShapeCell {
+    Stone,
+    Gap,
+}
+
+/**
+ * A pattern of stones and empty cells along a line, such as X-X, the
+ * stretch two. Shapes are matched in the direction the line runs, so an
+ * asymmetric shape and its reverse are different shapes.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub struct Shape {
+    cells : Vec<ShapeCell>,
+}
+
+impl Shape {
+    pub fn new(cells:&[ShapeCell]) -> Shape {
+        assert!(!cells.is_empty());
+        Shape { cells: cells.to_vec() }
+    }
+
+    // X for a stone and - for a gap, as in Line::of
+    pub fn of(s:&str) -> Shape {
+        let cells:Vec<ShapeCell> = s.chars().map(|c| match c {
+            'X' => ShapeCell::Stone,
+            '-' => ShapeCell::Gap,
+            _ => panic!("Shape strings must consist only of - and X (not {})", c),
+        }).collect();
+        Shape::new(&cells)
+    }
+}
+
+impl Line {
+    /**
+     * The number of places shape appears in the line as piece's stones
+     * and empty cells, counting only those inside some WIN_LENGTH window
+     * free of the opponent's stones, where the shape could still grow
+     * into a six.
+     */
+    pub fn count_shape(&self, piece:Piece, shape:&Shape) -> usize {
+        assert!(piece != Piece::Empty);
+        let cells = self.as_slice();
+        if self.size() < WIN_LENGTH || shape.cells.len() > WIN_LENGTH {
+            return 0
+        }
+        let clear:Vec<bool> = cells.windows(WIN_LENGTH)
+            .map(|window| !window.contains(&piece.opponent()))
+            .collect();
+        (0..=cells.len() - shape.cells.len()).filter(|&start| {
+            let matches = shape.cells.iter().zip(&cells[start..]).all(|(&want, &have)| match want {
+                ShapeCell::Stone => have == piece,
+                ShapeCell::Gap => have == Piece::Empty,
+            });
+            // the windows which hold the whole match
+            let first = (start + shape.cells.len()).saturating_sub(WIN_LENGTH);
+            let last = start.min(clear.len() - 1);
+            matches && clear[first..=last].iter().any(|&c| c)
+        }).count()
+    }
+}
+
+impl Board {
+    // count_shape summed over every line of the board
+    pub fn count_shape(&self, piece:Piece, shape:&Shape) -> usize {
+        (0..LINE_COUNT).map(|i| self.line(i).count_shape(piece, shape)).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Shape;
+    use super::ShapeCell;
+    use board::StandardBoard as Board;
+    use board::Line;
+    use board::Piece;
+    use board::CENTER;
+
+    #[test]
+    fn test_line_count_shape() {
+        let stretch = Shape::of("X-X");
+        assert_eq!(stretch, Shape::new(&[ShapeCell::Stone, ShapeCell::Gap, ShapeCell::Stone]));
+        assert_eq!(Line::of("--X-X--").count_shape(Piece::Black, &stretch), 1);
+        assert_eq!(Line::of("X-X-X-").count_shape(Piece::Black, &stretch), 2);
+        assert_eq!(Line::of("--XXX--").count_shape(Piece::Black, &stretch), 0);
+        assert_eq!(Line::of("--O-O--").count_shape(Piece::White, &stretch), 1);
+        // no window around the shape is free of White
+        assert_eq!(Line::of("OX-XO--").count_shape(Piece::Black, &stretch), 0);
+        assert_eq!(Line::of("OX-X---").count_shape(Piece::Black, &stretch), 1);
+        assert_eq!(Line::of("X-X").count_shape(Piece::Black, &stretch), 0);
+
+        let split = Shape::of("XX-X");
+        assert_eq!(Line::of("-XX-X--").count_shape(Piece::Black, &split), 1);
+        assert_eq!(Line::of("-X-XX--").count_shape(Piece::Black, &split), 0);
+    }
+
+    #[test]
+    fn test_count_shape() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER, CENTER + 2, Piece::Black);
+        let stretch = Shape::of("X-X");
+        assert_eq!(b.count_shape(Piece::Black, &stretch), 1);
+        assert_eq!(b.count_shape(Piece::White, &stretch), 0);
+        b.set(CENTER + 2, CENTER + 2, Piece::Black);
+        // along the column and the diagonal too
+        assert_eq!(b.count_shape(Piece::Black, &stretch), 3);
+        assert_eq!(b.count_shape(Piece::Black, &Shape::of("X")), 3 * 4);
+    }
+}
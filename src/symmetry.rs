@@ -0,0 +1,402 @@
+use std::collections::HashSet;
+
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use turn::Turn;
+
+const MAX : usize = BOARD_SIZE - 1;
+
+/**
+ * The eight rotations and reflections of the square board. Rotations
+ * are clockwise as the board is usually drawn, with the highest row
+ * at the top.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // swap left and right
+    ReflectHorizontal,
+    // swap top and bottom
+    ReflectVertical,
+    // swap rows and columns, fixing the main diagonal (0,0)->(MAX,MAX)
+    Transpose,
+    // fix the anti-diagonal (0,MAX)->(MAX,0)
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL : [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::ReflectHorizontal,
+        Symmetry::ReflectVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    // where the cell at row, col ends up under this transform
+    pub fn apply(self, row:usize, col:usize) -> (usize, usize) {
+        match self {
+            Symmetry::Identity          => (row, col),
+            Symmetry::Rotate90          => (MAX - col, row),
+            Symmetry::Rotate180         => (MAX - row, MAX - col),
+            Symmetry::Rotate270         => (col, MAX - row),
+            Symmetry::ReflectHorizontal => (row, MAX - col),
+            Symmetry::ReflectVertical   => (MAX - row, col),
+            Symmetry::Transpose         => (col, row),
+            Symmetry::AntiTranspose     => (MAX - col, MAX - row),
+        }
+    }
+}
+
+/**
+ * The distinct cells row, col maps to under the eight transforms, in the
+ * order of Symmetry::ALL and starting with row, col itself: just the one
+ * for the center, four for other cells on an axis or diagonal, and eight
+ * for the rest.
+ */
+pub fn symmetric_cells(row:usize, col:usize) -> Vec<(usize, usize)> {
+    let mut cells = Vec::with_capacity(Symmetry::ALL.len());
+    for &symmetry in Symmetry::ALL.iter() {
+        let cell = symmetry.apply(row, col);
+        if !cells.contains(&cell) {
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+// the board's stones after a transform, ordered so that comparing the keys
+// of two boards compares their cells in row-major order, with Empty before
+// Black before White
+type CellKey = Vec<(usize, u8)>;
+
+fn cell_key(stones:&[(usize, usize, Piece)], symmetry:Symmetry) -> CellKey {
+    let mut key:CellKey = stones.iter().map(|&(r, c, piece)| {
+        let (r, c) = symmetry.apply(r, c);
+        // an earlier stone makes the cells compare greater
+        let index = BOARD_SIZE * BOARD_SIZE - 1 - (r * BOARD_SIZE + c);
+        (index, if piece == Piece::Black { 1 } else { 2 })
+    }).collect();
+    key.sort_by(|a, b| b.cmp(a));
+    key
+}
+
+impl Board {
+    pub fn transform(&self, symmetry:Symmetry) -> Board {
+        let mut board = Board::empty();
+        for (r, c, piece) in self.stones() {
+            let (r, c) = symmetry.apply(r, c);
+            board.set(r, c, piece);
+        }
+        board
+    }
+
+    pub fn rotate90(&self) -> Board {
+        self.transform(Symmetry::Rotate90)
+    }
+
+    pub fn reflect_horizontal(&self) -> Board {
+        self.transform(Symmetry::ReflectHorizontal)
+    }
+
+    pub fn reflect_vertical(&self) -> Board {
+        self.transform(Symmetry::ReflectVertical)
+    }
+
+    /**
+     * The transforms which leave the board unchanged, in the order of
+     * Symmetry::ALL. The identity is always among them.
+     */
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::ALL.iter().cloned().filter(|&symmetry| {
+            self.stones().all(|(r, c, piece)| {
+                let (r, c) = symmetry.apply(r, c);
+                self.get(r, c) == piece
+            })
+        }).collect()
+    }
+
+    /**
+     * The transform giving the canonical form of the board, the first in
+     * Symmetry::ALL if several do.
+     */
+    pub fn canonical_symmetry(&self) -> Symmetry {
+        let stones:Vec<(usize, usize, Piece)> = self.stones().collect();
+        let mut best = (cell_key(&stones, Symmetry::Identity), Symmetry::Identity);
+        for &symmetry in Symmetry::ALL[1..].iter() {
+            let key = cell_key(&stones, symmetry);
+            if key < best.0 {
+                best = (key, symmetry);
+            }
+        }
+        best.1
+    }
+
+    /**
+     * The least of the board's eight transforms, comparing cells in
+     * row-major order with Empty before Black before White. Positions
+     * which are transforms of each other have the same canonical form.
+     */
+    pub fn canonical(&self) -> Board {
+        self.transform(self.canonical_symmetry())
+    }
+
+    /**
+     * The legal_turns of to_move, keeping only the first of any whose
+     * resulting positions have the same canonical form. Turns are
+     * generated as they are asked for, but every distinct position seen
+     * so far is remembered.
+     */
+    pub fn legal_turns_canonical(&self, to_move:Piece) -> impl Iterator<Item = Turn> {
+        self.distinct_positions(self.legal_turns(to_move), to_move)
+    }
+
+    /**
+     * Roughly how many turns a search of to_move's options has to try:
+     * the nearby_turns within radius, counting turns giving the same
+     * canonical position once.
+     */
+    pub fn branching_estimate(&self, to_move:Piece, radius:usize) -> usize {
+        self.distinct_positions(self.nearby_turns(to_move, radius), to_move).count()
+    }
+
+    // the turns, keeping only the first of any whose resulting positions
+    // have the same canonical form
+    fn distinct_positions<I:Iterator<Item = Turn>>(&self, turns:I, to_move:Piece) -> impl Iterator<Item = Turn> {
+        let stones:Vec<(usize, usize, Piece)> = self.stones().collect();
+        let mut seen = HashSet::new();
+        turns.filter(move |turn| {
+            let mut after = stones.clone();
+            after.extend(turn.cells().iter().map(|&(r, c)| (r, c, to_move)));
+            let key = Symmetry::ALL.iter().map(|&symmetry| cell_key(&after, symmetry)).min();
+            seen.insert(key)
+        })
+    }
+
+    // legal_turns_canonical, collected
+    pub fn distinct_turns(&self, to_move:Piece) -> Vec<Turn> {
+        self.legal_turns_canonical(to_move).collect()
+    }
+
+    /**
+     * Whether other is this board transformed by axis with its colors
+     * swapped, as when one player copies the other's moves.
+     */
+    pub fn is_mirror_of(&self, other:&Board, axis:Symmetry) -> bool {
+        (0..BOARD_SIZE).all(|r| (0..BOARD_SIZE).all(|c| {
+            let (mr, mc) = axis.apply(r, c);
+            other.get(mr, mc) == self.get(r, c).opponent()
+        }))
+    }
+
+    /**
+     * A copy of the board with every black stone made white and every
+     * white stone made black, to see the position from the other side.
+     */
+    pub fn flipped(&self) -> Board {
+        let mut board = Board::empty();
+        for (r, c, piece) in self.stones() {
+            board.set(r, c, piece.opponent());
+        }
+        board
+    }
+
+    #[deprecated(note = "renamed to flipped")]
+    pub fn swap_colors(&self) -> Board {
+        self.flipped()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Symmetry;
+    use super::symmetric_cells;
+    use turn::Turn;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_apply() {
+        const MAX:usize = BOARD_SIZE - 1;
+        // the top left corner, as drawn
+        let corner = (MAX, 0);
+        assert_eq!(Symmetry::Rotate90.apply(corner.0, corner.1), (MAX, MAX));
+        assert_eq!(Symmetry::Rotate180.apply(corner.0, corner.1), (0, MAX));
+        assert_eq!(Symmetry::Rotate270.apply(corner.0, corner.1), (0, 0));
+        assert_eq!(Symmetry::ReflectHorizontal.apply(corner.0, corner.1), (MAX, MAX));
+        assert_eq!(Symmetry::ReflectVertical.apply(corner.0, corner.1), (0, 0));
+        assert_eq!(Symmetry::Transpose.apply(corner.0, corner.1), (0, MAX));
+        assert_eq!(Symmetry::AntiTranspose.apply(corner.0, corner.1), corner);
+        for &s in Symmetry::ALL.iter() {
+            assert_eq!(s.apply(CENTER, CENTER), (CENTER, CENTER));
+        }
+    }
+
+    #[test]
+    fn test_symmetric_cells() {
+        assert_eq!(symmetric_cells(CENTER, CENTER), vec![(CENTER, CENTER)]);
+        assert_eq!(symmetric_cells(1, 2).len(), 8);
+        assert_eq!(symmetric_cells(1, 2)[0], (1, 2));
+        assert_eq!(symmetric_cells(CENTER, 0).len(), 4);
+        assert_eq!(symmetric_cells(3, 3).len(), 4);
+        assert!(symmetric_cells(0, 0).contains(&(BOARD_SIZE - 1, BOARD_SIZE - 1)));
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut b = Board::empty();
+        b.set(1, 2, Piece::Black);
+        b.set(CENTER, CENTER, Piece::White);
+        let r = b.rotate90();
+        assert_eq!(r.get(BOARD_SIZE - 3, 1), Piece::Black);
+        assert_eq!(r.get(CENTER, CENTER), Piece::White);
+        assert_eq!(r.count(Piece::Black), 1);
+        assert_eq!(b.rotate90().rotate90().rotate90().rotate90().to_ascii_grid(), b.to_ascii_grid());
+
+        let s = b.flipped();
+        assert_eq!(s.get(1, 2), Piece::White);
+        assert_eq!(s.get(CENTER, CENTER), Piece::Black);
+        assert_eq!(s.count(Piece::Empty), b.count(Piece::Empty));
+    }
+
+    #[test]
+    fn test_flipped() {
+        assert_eq!(Piece::Black.opponent(), Piece::White);
+        assert_eq!(Piece::White.opponent(), Piece::Black);
+        assert_eq!(Piece::Empty.opponent(), Piece::Empty);
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 1, Piece::White);
+        let flipped = b.flipped();
+        assert_eq!(flipped.get(CENTER, CENTER), Piece::White);
+        assert!(flipped != b);
+        assert_eq!(flipped.count(Piece::Black), 2);
+        assert!(flipped.flipped() == b);
+    }
+
+    #[test]
+    fn test_is_mirror_of() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 1, CENTER + 3, Piece::White);
+        b.set(2, 5, Piece::Black);
+        let mirror = b.reflect_horizontal().flipped();
+        assert!(b.is_mirror_of(&mirror, Symmetry::ReflectHorizontal));
+        assert!(mirror.is_mirror_of(&b, Symmetry::ReflectHorizontal));
+        assert!(!b.is_mirror_of(&mirror, Symmetry::ReflectVertical));
+        assert!(!b.is_mirror_of(&b.reflect_horizontal(), Symmetry::ReflectHorizontal));
+
+        let mut near_miss = mirror;
+        near_miss.set(0, 0, Piece::White);
+        assert!(!b.is_mirror_of(&near_miss, Symmetry::ReflectHorizontal));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut b = Board::empty();
+        b.set(1, 2, Piece::Black);
+        b.set(CENTER, CENTER, Piece::White);
+        let canonical = b.canonical().to_ascii_grid();
+        for &s in Symmetry::ALL.iter() {
+            assert_eq!(b.transform(s).canonical().to_ascii_grid(), canonical);
+        }
+        // the stone nearest the end of row-major order
+        assert_eq!(b.canonical().get(BOARD_SIZE - 2, BOARD_SIZE - 3), Piece::Black);
+        assert_eq!(Board::empty().canonical_symmetry(), Symmetry::Identity);
+    }
+
+    #[test]
+    fn test_canonical_hashing() {
+        use std::collections::HashSet;
+
+        let mut b = Board::empty();
+        b.set(1, 2, Piece::Black);
+        b.set(4, 0, Piece::White);
+        b.set(CENTER, CENTER + 3, Piece::Black);
+        let forms:Vec<Board> = Symmetry::ALL.iter().map(|&s| b.transform(s)).collect();
+        // the position has no symmetry, so its eight forms are all different
+        assert_eq!(forms.iter().cloned().collect::<HashSet<Board>>().len(), 8);
+        let canonical:HashSet<Board> = forms.iter().map(|form| form.canonical()).collect();
+        assert_eq!(canonical.len(), 1);
+        assert!(canonical.contains(&b.canonical()));
+        assert!(forms.iter().all(|form| form.canonical().zobrist() == b.canonical().zobrist()));
+        assert!(forms.contains(&b.canonical()));
+    }
+
+    #[test]
+    fn test_distinct_turns() {
+        // the orbits of single cells, by Burnside's lemma
+        let fixed = BOARD_SIZE * BOARD_SIZE + 3 + 4 * BOARD_SIZE;
+        assert_eq!(Board::empty().distinct_turns(Piece::Black).len(), fixed / 8);
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let empty = BOARD_SIZE * BOARD_SIZE - 1;
+        let raw = empty * (empty - 1) / 2;
+        let distinct = b.distinct_turns(Piece::White);
+        assert!(distinct.len() * 7 < raw);
+        assert!(distinct.len() * 8 >= raw);
+        assert_eq!(distinct[0], Turn::pair((0, 0), (0, 1)));
+        assert_eq!(b.legal_turns(Piece::White).count(), raw);
+        assert!(b.legal_turns_canonical(Piece::White).take(10).eq(distinct.iter().cloned().take(10)));
+    }
+
+    #[test]
+    fn test_branching_estimate() {
+        // the opening stone within one of the center, up to symmetry
+        assert_eq!(Board::empty().branching_estimate(Piece::Black, 1), 3);
+        assert_eq!(Board::empty().branching_estimate(Piece::Black, 0), 1);
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let opening = b.branching_estimate(Piece::White, 2);
+        assert!(opening > 0);
+        assert!(opening < b.branching_estimate(Piece::White, 3));
+
+        // fill all but a 3x3 block in the middle
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                if r.abs_diff(CENTER) > 1 || c.abs_diff(CENTER) > 1 {
+                    b.set(r, c, if (r + c) % 2 == 0 { Piece::Black } else { Piece::White });
+                }
+            }
+        }
+        let crowded = b.branching_estimate(Piece::White, 2);
+        assert!(crowded < opening);
+        assert!(crowded <= 9 * 8 / 2);
+        for c in CENTER - 1..=CENTER + 1 {
+            b.set(CENTER - 1, c, Piece::Black);
+            b.set(CENTER + 1, c, Piece::White);
+        }
+        assert!(b.branching_estimate(Piece::White, 2) < crowded);
+        b.set(CENTER, CENTER - 1, Piece::Black);
+        assert_eq!(b.branching_estimate(Piece::White, 2), 1);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let mut b = Board::empty();
+        assert_eq!(b.symmetries(), Symmetry::ALL.to_vec());
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.symmetries().len(), 8);
+        b.set(CENTER+1, CENTER+1, Piece::White);
+        assert_eq!(b.symmetries(), vec![Symmetry::Identity, Symmetry::Transpose]);
+        b.set(CENTER+1, CENTER+2, Piece::White);
+        assert_eq!(b.symmetries(), vec![Symmetry::Identity]);
+
+        let mut b = Board::empty();
+        b.set(1, 2, Piece::Black);
+        assert_eq!(b.symmetries(), vec![Symmetry::Identity]);
+    }
+}
@@ -0,0 +1,1513 @@
+use board::chebyshev_distance;
+use board::line_count;
+use board::windows_through_on;
+use board::Board;
+use board::Line;
+use board::Piece;
+use board::DIRECTIONS;
+use board::WIN_LENGTH;
+use turn::Turn;
+
+/**
+ * A WIN_LENGTH window holding WIN_LENGTH-2 or more of piece's stones,
+ * none of the opponent's and at least one empty cell.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Threat {
+    piece : Piece,
+    cells : [(usize, usize); WIN_LENGTH],
+}
+
+impl Threat {
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+}
+
+/**
+ * What a turn does tactically: whether it adds to the threats of the
+ * player making it, takes away from the opponent's, or both.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TurnRole {
+    Attack,
+    Defense,
+    Both,
+    Neutral,
+}
+
+/**
+ * How a game ends: one color wins, or the board fills without a six.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Outcome {
+    Winner(Piece),
+    Draw,
+}
+
+/**
+ * The solid runs of a line which threaten to win, from Line::count_threats.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Threats {
+    // runs of WIN_LENGTH-1 with an empty cell at one end or both
+    pub fives : usize,
+    // runs of WIN_LENGTH-2 with an empty cell at both ends, which can be
+    // made six in more than one window
+    pub live_fours : usize,
+}
+
+impl<const N: usize> Line<N> {
+    /**
+     * Count the solid fives and live fours piece has in the line, such
+     * as -XXXXX- and --XXXX--. A run blocked at both ends by the
+     * opponent or the edge, or a four with only one window to grow in,
+     * isn't counted. Broken shapes such as XX-XXX are left to threats().
+     */
+    pub fn count_threats(&self, piece:Piece) -> Threats {
+        assert!(piece != Piece::Empty);
+        let cells = self.as_slice();
+        let empty = |i:Option<usize>| i.and_then(|i| cells.get(i)) == Some(&Piece::Empty);
+        let mut counts = Threats { fives: 0, live_fours: 0 };
+        let mut start = 0;
+        while start < cells.len() {
+            if cells[start] != piece {
+                start += 1;
+                continue
+            }
+            let end = start + cells[start..].iter().take_while(|&&p| p == piece).count();
+            let (before, after) = (start.checked_sub(1), Some(end));
+            if end - start == WIN_LENGTH - 1 && (empty(before) || empty(after)) {
+                counts.fives += 1;
+            }
+            if end - start == WIN_LENGTH - 2 && empty(before) && empty(after) {
+                // the windows holding the run and none of the opponent's
+                let open = (end.saturating_sub(WIN_LENGTH)..=start)
+                    .filter(|&s| s + WIN_LENGTH <= cells.len())
+                    .filter(|&s| !cells[s..s + WIN_LENGTH].contains(&piece.opponent()))
+                    .count();
+                if open >= 2 {
+                    counts.live_fours += 1;
+                }
+            }
+            start = end;
+        }
+        counts
+    }
+
+    /**
+     * The number of threats piece has in this line: the fewest stones
+     * the opponent must place in the line to block every window holding
+     * WIN_LENGTH-2 or more of piece's stones and none of the opponent's.
+     * A live four such as --XXXX-- is two threats, as is an open five,
+     * while a four or five closed at one end is one.
+     */
+    pub fn threats(&self, piece:Piece) -> usize {
+        assert!(piece != Piece::Empty);
+        if self.size() < WIN_LENGTH {
+            return 0
+        }
+        // the rightmost cell the opponent is assumed to have blocked
+        let mut blocked:Option<usize> = None;
+        let mut threats = 0;
+        for start in 0..self.size() - WIN_LENGTH + 1 {
+            let mut own = 0;
+            let mut open = blocked.is_none_or(|b| b < start);
+            let mut last_empty = None;
+            for i in start..start + WIN_LENGTH {
+                match self.get(i) {
+                    Piece::Empty => last_empty = Some(i),
+                    p if p == piece => own += 1,
+                    _ => open = false,
+                }
+            }
+            if open && own >= WIN_LENGTH - 2 && last_empty.is_some() {
+                // blocking the last empty cell also blocks as many of
+                // the later windows as any choice could
+                blocked = last_empty;
+                threats += 1;
+            }
+        }
+        threats
+    }
+}
+
+impl<const N: usize> Board<N> {
+    /**
+     * The total number of threats piece has on the board, summed over
+     * every line.
+     */
+    pub fn threat_count(&self, piece:Piece) -> usize {
+        (0..line_count(N)).map(|i| self.line(i).threats(piece)).sum()
+    }
+
+    /**
+     * The threat_count split by the direction of the lines: rows, columns,
+     * up diagonals and down diagonals, in the order of Board::line.
+     */
+    pub fn threats_by_direction(&self, piece:Piece) -> [usize; 4] {
+        // the first line index of each direction after the rows
+        let starts = [N, 2 * N, 2 * N + (2 * N - 1)];
+        let mut counts = [0; 4];
+        for i in 0..line_count(N) {
+            let direction = starts.iter().filter(|&&start| i >= start).count();
+            counts[direction] += self.line(i).threats(piece);
+        }
+        counts
+    }
+
+    /**
+     * Every window which is a threat for piece, in the order of
+     * six_windows. A live four is several overlapping threats.
+     */
+    pub fn find_threats(&self, piece:Piece) -> Vec<Threat> {
+        assert!(piece != Piece::Empty);
+        self.six_windows()
+            .filter(|window| self.is_threat(window, piece))
+            .map(|cells| Threat { piece, cells })
+            .collect()
+    }
+
+    // the threats for piece in windows through any of the cells, each once
+    fn threats_through(&self, cells:&[(usize, usize)], piece:Piece) -> Vec<Threat> {
+        let mut threats:Vec<Threat> = Vec::new();
+        for &(row, col) in cells {
+            for window in windows_through_on(N, row, col) {
+                if self.is_threat(&window, piece) && !threats.iter().any(|t| t.cells == window) {
+                    threats.push(Threat { piece, cells: window });
+                }
+            }
+        }
+        threats
+    }
+
+    fn is_threat(&self, window:&[(usize, usize)], piece:Piece) -> bool {
+        let mut own = 0;
+        for &(r, c) in window {
+            match self.get(r, c) {
+                Piece::Empty => (),
+                p if p == piece => own += 1,
+                _ => return false,
+            }
+        }
+        (WIN_LENGTH - 2..WIN_LENGTH).contains(&own)
+    }
+
+    /**
+     * The empty cell of every window holding WIN_LENGTH-1 of piece's
+     * stones, where a single stone completes a six, whether it is in the
+     * middle as in XXX-XX or at an end. Each cell is listed once, in the
+     * order of six_windows.
+     */
+    pub fn gap_threats(&self, piece:Piece) -> Vec<(usize, usize)> {
+        let mut gaps = Vec::new();
+        for threat in self.find_threats(piece) {
+            let empty = self.blocks_for(&threat);
+            if empty.len() == 1 && !gaps.contains(&empty[0]) {
+                gaps.push(empty[0]);
+            }
+        }
+        gaps
+    }
+
+    /**
+     * The stones of every open three of piece's: three in a row with an
+     * empty cell at each end, inside some window free of the opponent's
+     * stones. With broken, threes with one empty cell inside them, as in
+     * -X-XX-, are included too. They are listed by direction, then by
+     * their first stone in row-major order.
+     */
+    pub fn open_threes(&self, piece:Piece, broken:bool) -> Vec<[(usize, usize); 3]> {
+        assert!(piece != Piece::Empty);
+        let opponent = piece.opponent();
+        // the solid three, then the broken ones, by the offset of any gap
+        let shapes:&[Option<i32>] = if broken { &[None, Some(1), Some(2)] } else { &[None] };
+        let mut threes = Vec::new();
+        for &(rstride, cstride) in DIRECTIONS.iter() {
+            for row in 0..N {
+                for col in 0..N {
+                    let at = |k:i32| -> Option<(usize, usize)> {
+                        let (r, c) = (row as i32 + k * rstride, col as i32 + k * cstride);
+                        if r < 0 || r >= N as i32 || c < 0 || c >= N as i32 {
+                            None
+                        } else {
+                            Some((r as usize, c as usize))
+                        }
+                    };
+                    let is = |k:i32, p:Piece| at(k).is_some_and(|(r, c)| self.get(r, c) == p);
+                    for &gap in shapes {
+                        let len = if gap.is_some() { 4 } else { 3 };
+                        let stones:Vec<i32> = (0..len).filter(|&k| Some(k) != gap).collect();
+                        if !stones.iter().all(|&k| is(k, piece)) || gap.is_some_and(|g| !is(g, Piece::Empty)) {
+                            continue
+                        }
+                        if !is(-1, Piece::Empty) || !is(len, Piece::Empty) {
+                            continue
+                        }
+                        let winnable = (len - WIN_LENGTH as i32..=0).any(|start| {
+                            (start..start + WIN_LENGTH as i32).all(|k| at(k).is_some() && !is(k, opponent))
+                        });
+                        if winnable {
+                            let mut three = [(0, 0); 3];
+                            for (cell, &k) in three.iter_mut().zip(stones.iter()) {
+                                *cell = at(k).unwrap();
+                            }
+                            threes.push(three);
+                        }
+                    }
+                }
+            }
+        }
+        threes
+    }
+
+    /**
+     * The empty cells of the threat's window, any one of which the
+     * opponent can take to leave it short of six.
+     */
+    pub fn blocks_for(&self, threat:&Threat) -> Vec<(usize, usize)> {
+        threat.cells().iter()
+            .filter(|&&(r, c)| self.get(r, c) == Piece::Empty)
+            .cloned()
+            .collect()
+    }
+
+    /**
+     * Every pair of empty cells, each in row-major order, which between
+     * them block all of attacker's threats. Empty either when no two
+     * stones are enough, so that the defender has lost, or when attacker
+     * has no threats to block, which threat_count tells apart.
+     */
+    pub fn jointly_blockable(&self, attacker:Piece) -> Vec<((usize, usize), (usize, usize))> {
+        assert!(attacker != Piece::Empty);
+        let threats = self.find_threats(attacker);
+        let mut cells:Vec<(usize, usize)> = threats.iter().flat_map(|t| self.blocks_for(t)).collect();
+        cells.sort();
+        cells.dedup();
+        let mut pairs = Vec::new();
+        for (i, &a) in cells.iter().enumerate() {
+            for &b in &cells[i + 1..] {
+                if threats.iter().all(|t| t.cells().contains(&a) || t.cells().contains(&b)) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    /**
+     * Whether to_move has already lost: the opponent has won, or will win
+     * next turn however to_move places its two stones. That is, to_move
+     * can't complete a six itself, and no two cells cover every one of the
+     * opponent's threats.
+     */
+    pub fn is_lost(&self, to_move:Piece) -> bool {
+        assert!(to_move != Piece::Empty);
+        let opponent = to_move.opponent();
+        if self.min_stones_to_win(opponent) == Some(0) {
+            return true
+        }
+        if self.min_stones_to_win(to_move).is_some_and(|n| n <= 2) {
+            return false
+        }
+        self.unblockable(&self.find_threats(opponent))
+    }
+
+    // whether no two cells between them block every one of the threats
+    fn unblockable(&self, threats:&[Threat]) -> bool {
+        let first = match threats.first() {
+            Some(first) => first,
+            None => return false,
+        };
+        // any defense has to block the first threat with one of its stones
+        for a in self.blocks_for(first) {
+            let remaining:Vec<&Threat> = threats.iter().filter(|t| !t.cells().contains(&a)).collect();
+            let next = match remaining.first() {
+                Some(next) => next,
+                None => return false,
+            };
+            if self.blocks_for(next).iter().any(|b| remaining.iter().all(|t| t.cells().contains(b))) {
+                return false
+            }
+        }
+        true
+    }
+
+    /**
+     * The result if the game is over: someone has six in a row, or the
+     * board is full without one. Unlike trivial_result this doesn't look
+     * ahead to the next turn.
+     */
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self.winner() {
+            Some(winner) => Some(Outcome::Winner(winner)),
+            None if self.is_full() => Some(Outcome::Draw),
+            None => None,
+        }
+    }
+
+    /**
+     * The result of the game if it is already decided without search:
+     * someone has six in a row, to_move can complete one this turn, the
+     * opponent will complete one next turn whatever to_move does (see
+     * is_lost), or the board is full. None if the game is still open.
+     */
+    pub fn trivial_result(&self, to_move:Piece) -> Option<Outcome> {
+        assert!(to_move != Piece::Empty);
+        if let Some(winner) = self.winner() {
+            return Some(Outcome::Winner(winner))
+        }
+        if self.winning_turn(to_move).is_some() {
+            return Some(Outcome::Winner(to_move))
+        }
+        if self.is_lost(to_move) {
+            return Some(Outcome::Winner(to_move.opponent()))
+        }
+        if self.is_full() {
+            return Some(Outcome::Draw)
+        }
+        None
+    }
+
+    /**
+     * Empty cells which between them touch every window the opponent
+     * could still fill, so that with defender's stones on all of them the
+     * opponent could never make six. None if the opponent already has.
+     * The cells are chosen greedily, each touching as many of the
+     * remaining windows as any other (the first such in row-major
+     * order), so the set is small but not always the smallest. They are
+     * returned in the order chosen.
+     */
+    pub fn drawing_cells(&self, defender:Piece) -> Option<Vec<(usize, usize)>> {
+        assert!(defender != Piece::Empty);
+        let mut open:Vec<Vec<(usize, usize)>> = Vec::new();
+        for window in self.six_windows() {
+            if window.iter().any(|&(r, c)| self.get(r, c) == defender) {
+                continue
+            }
+            let empty:Vec<(usize, usize)> = window.iter().cloned()
+                .filter(|&(r, c)| self.get(r, c) == Piece::Empty)
+                .collect();
+            if empty.is_empty() {
+                return None
+            }
+            open.push(empty);
+        }
+
+        let mut cells = Vec::new();
+        while !open.is_empty() {
+            let mut touching = [[0; N]; N];
+            for &(r, c) in open.iter().flatten() {
+                touching[r][c] += 1;
+            }
+            let mut best = (0, (0, 0));
+            for (r, row) in touching.iter().enumerate() {
+                for (c, &n) in row.iter().enumerate() {
+                    if n > best.0 {
+                        best = (n, (r, c));
+                    }
+                }
+            }
+            let cell = best.1;
+            open.retain(|window| !window.contains(&cell));
+            cells.push(cell);
+        }
+        Some(cells)
+    }
+
+    /**
+     * Whether to_move has to spend this turn blocking: the opponent has a
+     * threat, which would complete a six next turn, and to_move can't win
+     * first.
+     */
+    pub fn requires_response(&self, to_move:Piece) -> bool {
+        assert!(to_move != Piece::Empty);
+        if self.min_stones_to_win(to_move).is_some_and(|n| n <= 2) {
+            return false
+        }
+        self.threat_count(to_move.opponent()) > 0
+    }
+
+    /**
+     * Whether the position is tactically quiet, so that a static
+     * evaluation can be trusted: neither side has a threat now, nor a
+     * window free of the other's stones which its next turn's two stones
+     * could bring to a threat. That holds for both sides alike, so the
+     * answer is the same whoever is to move.
+     */
+    pub fn is_quiet(&self, to_move:Piece) -> bool {
+        assert!(to_move != Piece::Empty);
+        [to_move, to_move.opponent()].iter().all(|&piece| {
+            self.threat_count(piece) == 0 && self.open_cells(piece, WIN_LENGTH - 4).is_empty()
+        })
+    }
+
+    /**
+     * How many threats a stone of piece at row, col would add in the
+     * four lines through it, or 0 if the cell is occupied.
+     */
+    pub fn new_threats(&self, row:usize, col:usize, piece:Piece) -> usize {
+        if self.get(row, col) != Piece::Empty {
+            return 0
+        }
+        let count = |board:&Board<N>| -> usize {
+            board.lines_through(row, col).iter().map(|line| line.threats(piece)).sum()
+        };
+        let mut board = *self;
+        let before = count(&board);
+        board.set(row, col, piece);
+        count(&board).saturating_sub(before)
+    }
+
+    /**
+     * Classify to_move's turn by how it changes the threat counts: an
+     * attack if it gives to_move more threats, a defense if it leaves
+     * the opponent fewer.
+     */
+    pub fn classify_turn(&self, turn:&Turn, to_move:Piece) -> TurnRole {
+        assert!(to_move != Piece::Empty);
+        let opponent = to_move.opponent();
+        let mut after = *self;
+        after.make_move(turn, to_move);
+        let attack = after.threat_count(to_move) > self.threat_count(to_move);
+        let defense = after.threat_count(opponent) < self.threat_count(opponent);
+        match (attack, defense) {
+            (true, true) => TurnRole::Both,
+            (true, false) => TurnRole::Attack,
+            (false, true) => TurnRole::Defense,
+            (false, false) => TurnRole::Neutral,
+        }
+    }
+
+    /**
+     * How many more threats the turn's two stones give to_move together
+     * than they would one at a time, as when two stones added to a pair
+     * make a four where either alone makes only a three. 0 for a single
+     * stone turn, or stones which work independently.
+     */
+    pub fn turn_synergy(&self, turn:&Turn, to_move:Piece) -> i32 {
+        assert!(to_move != Piece::Empty);
+        if turn.size() < 2 {
+            return 0
+        }
+        let before = self.threat_count(to_move) as i32;
+        let gain = |cells:&[(usize, usize)]| -> i32 {
+            let mut board = *self;
+            for &(r, c) in cells {
+                board.set(r, c, to_move);
+            }
+            board.threat_count(to_move) as i32 - before
+        };
+        let cells = turn.cells();
+        (gain(cells) - gain(&cells[..1]) - gain(&cells[1..])).max(0)
+    }
+
+    /**
+     * The empty cell where a single stone most reduces opponent's
+     * threats, preferring cells nearer the center among equally good
+     * ones, or None if opponent has no threats to reduce.
+     */
+    pub fn best_defense(&self, opponent:Piece) -> Option<(usize, usize)> {
+        assert!(opponent != Piece::Empty);
+        if self.threat_count(opponent) == 0 {
+            return None
+        }
+        // only the lines through the blocking stone can change
+        let count = |board:&Board<N>, row:usize, col:usize| -> usize {
+            board.lines_through(row, col).iter().map(|line| line.threats(opponent)).sum()
+        };
+        let center = N / 2;
+        let mut board = *self;
+        let mut best = None;
+        for r in 0..N {
+            for c in 0..N {
+                if self.get(r, c) != Piece::Empty {
+                    continue
+                }
+                let before = count(&board, r, c);
+                board.set(r, c, opponent.opponent());
+                let key = (before - count(&board, r, c), center - chebyshev_distance((r, c), (center, center)));
+                board.set(r, c, Piece::Empty);
+                if best.is_none_or(|(best_key, _)| key > best_key) {
+                    best = Some((key, (r, c)));
+                }
+            }
+        }
+        best.map(|(_, cell)| cell)
+    }
+
+    /**
+     * Candidate turns for to_move when the opponent has threats: one stone
+     * blocks the threat nearest completion (the first of those equally
+     * near), and the other blocks another threat or joins a window of
+     * to_move's holding two or more stones, free of the opponent's, or
+     * failing both goes on any empty cell. Empty if the opponent has no
+     * threats.
+     */
+    pub fn defensive_turns(&self, to_move:Piece) -> Vec<Turn> {
+        assert!(to_move != Piece::Empty);
+        let opponent = to_move.opponent();
+        let threats = self.find_threats(opponent);
+        let blocks = match threats.iter().min_by_key(|t| self.blocks_for(t).len()) {
+            Some(top) => self.blocks_for(top),
+            None => return Vec::new(),
+        };
+        let mut seconds = self.open_cells(opponent, WIN_LENGTH - 2);
+        for cell in self.open_cells(to_move, WIN_LENGTH - 4) {
+            if !seconds.contains(&cell) {
+                seconds.push(cell);
+            }
+        }
+        let mut turns = Vec::new();
+        for &a in &blocks {
+            for &b in &seconds {
+                // a pair of blocks would otherwise be listed both ways round
+                if b == a || (blocks.contains(&b) && b < a) {
+                    continue
+                }
+                turns.push(Turn::pair(a, b));
+            }
+        }
+        if turns.is_empty() {
+            // nothing else is worth playing, so any other empty cell will do
+            let a = blocks[0];
+            turns.push(match self.other_empty_cell(a) {
+                Some(b) => Turn::pair(a, b),
+                None => Turn::single(a.0, a.1),
+            });
+        }
+        turns
+    }
+
+    /**
+     * Search for a sequence of turns which wins for to_move however the
+     * opponent defends, using at most max_depth of to_move's turns. Only
+     * forcing turns are tried: each must leave to_move with two or more
+     * threats, so that the opponent has to spend both stones blocking.
+     * The sequence alternates to_move's turns with the opponent's, and
+     * ends with the winning turn. Where several defenses lose, the line
+     * follows the first one tried.
+     */
+    pub fn find_forced_win(&self, to_move:Piece, max_depth:usize) -> Option<Vec<Turn>> {
+        assert!(to_move != Piece::Empty);
+        if max_depth == 0 {
+            return None
+        }
+        if let Some(turn) = self.winning_turn(to_move) {
+            return Some(vec![turn])
+        }
+        let defender = to_move.opponent();
+        if max_depth == 1 || self.threat_count(defender) > 0 {
+            return None
+        }
+
+        let mut board = *self;
+        let candidates = self.open_cells(to_move, WIN_LENGTH - 4);
+        for (i, &a) in candidates.iter().enumerate() {
+            for &b in &candidates[i + 1..] {
+                let attack = Turn::pair(a, b);
+                let undo = board.make_move(&attack, to_move);
+                if board.threat_count(to_move) >= 2 {
+                    if let Some(line) = board.refute_all_defenses(to_move, max_depth - 1) {
+                        let mut win = vec![attack];
+                        win.extend(line);
+                        return Some(win)
+                    }
+                }
+                board.unmake_move(undo);
+            }
+        }
+        None
+    }
+
+    // to_move has just attacked; check that every blocking reply still
+    // loses, returning the defense tried first followed by the win
+    fn refute_all_defenses(&self, to_move:Piece, depth:usize) -> Option<Vec<Turn>> {
+        let defender = to_move.opponent();
+        let mut board = *self;
+        let mut defenses = self.blocking_turns(to_move, &self.find_threats(to_move));
+        if defenses.is_empty() {
+            // nothing blocks every threat, so any defense will do
+            defenses.extend(self.defensive_turns(defender).into_iter().take(1));
+        }
+        let mut principal = None;
+        for defense in defenses {
+            let undo = board.make_move(&defense, defender);
+            let line = board.find_forced_win(to_move, depth)?;
+            if principal.is_none() {
+                let mut moves = vec![defense];
+                moves.extend(line);
+                principal = Some(moves);
+            }
+            board.unmake_move(undo);
+        }
+        principal
+    }
+
+    // every turn which blocks all of attacker's threats: a pair of their
+    // cells, or one cell which blocks them alone with the other stone
+    // free. That one only matters in an open window holding two or more
+    // of attacker's stones, which a later attack might build on, or three
+    // of the defender's, where it makes a threat
+    fn blocking_turns(&self, attacker:Piece, threats:&[Threat]) -> Vec<Turn> {
+        let mut cells = Vec::new();
+        for threat in threats {
+            for cell in self.blocks_for(threat) {
+                if !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells.sort();
+        let mut free = self.open_cells(attacker, WIN_LENGTH - 4);
+        for cell in self.open_cells(attacker.opponent(), WIN_LENGTH - 3) {
+            if !free.contains(&cell) {
+                free.push(cell);
+            }
+        }
+        free.retain(|cell| !cells.contains(cell));
+        free.sort();
+        let blocked_by = |a:&(usize, usize), b:&(usize, usize)| {
+            threats.iter().all(|t| t.cells().contains(a) || t.cells().contains(b))
+        };
+        let mut turns = Vec::new();
+        for (i, &a) in cells.iter().enumerate() {
+            for &b in &cells[i + 1..] {
+                if blocked_by(&a, &b) {
+                    turns.push(Turn::pair(a, b));
+                }
+            }
+            if blocked_by(&a, &a) {
+                turns.extend(free.iter().map(|&b| Turn::pair(a, b)));
+            }
+        }
+        turns
+    }
+
+    /**
+     * Search for a victory by continuous fours: a sequence of attacker's
+     * turns, at most max_depth of them, each making a threat which the
+     * defender has to block, and ending with a six. Unlike
+     * find_forced_win a single threat is enough, and the defender's
+     * replies are the turns which block every threat. Where none can the
+     * defense shown is the first of defensive_turns. The sequence
+     * alternates the players' turns as for find_forced_win.
+     */
+    pub fn find_vcf(&self, attacker:Piece, max_depth:usize) -> Option<Vec<Turn>> {
+        assert!(attacker != Piece::Empty);
+        if max_depth == 0 {
+            return None
+        }
+        if let Some(turn) = self.winning_turn(attacker) {
+            return Some(vec![turn])
+        }
+        let defender = attacker.opponent();
+        if max_depth == 1 || self.threat_count(defender) > 0 {
+            return None
+        }
+
+        let mut board = *self;
+        let candidates = self.open_cells(attacker, WIN_LENGTH - 4);
+        for (i, &a) in candidates.iter().enumerate() {
+            for &b in &candidates[i + 1..] {
+                let attack = Turn::pair(a, b);
+                let undo = board.make_move(&attack, attacker);
+                // attacker had no threats, so any new one runs through a or b
+                let threats = board.threats_through(&[a, b], attacker);
+                if !threats.is_empty() {
+                    if let Some(line) = board.refute_blocks(attacker, &threats, max_depth - 1) {
+                        let mut win = vec![attack];
+                        win.extend(line);
+                        return Some(win)
+                    }
+                }
+                board.unmake_move(undo);
+            }
+        }
+        None
+    }
+
+    // attacker has just made these threats, its only ones; check that
+    // every block still loses to a vcf, returning the block tried first
+    // followed by the win
+    fn refute_blocks(&self, attacker:Piece, threats:&[Threat], depth:usize) -> Option<Vec<Turn>> {
+        let defender = attacker.opponent();
+        let blocks = if self.unblockable(threats) {
+            self.defensive_turns(defender).into_iter().take(1).collect()
+        } else if depth <= 1 {
+            // a block leaves no threat to complete next turn
+            return None
+        } else {
+            self.blocking_turns(attacker, threats)
+        };
+        if blocks.is_empty() {
+            return None
+        }
+        let mut board = *self;
+        let mut principal = None;
+        for block in blocks {
+            let undo = board.make_move(&block, defender);
+            let line = board.find_vcf(attacker, depth)?;
+            if principal.is_none() {
+                let mut moves = vec![block];
+                moves.extend(line);
+                principal = Some(moves);
+            }
+            board.unmake_move(undo);
+        }
+        principal
+    }
+
+    // a turn which completes six in a row for piece, if there is one
+    fn winning_turn(&self, piece:Piece) -> Option<Turn> {
+        for window in self.six_windows() {
+            let empty:Vec<(usize, usize)> = window.iter().cloned()
+                .filter(|&(r, c)| self.get(r, c) == Piece::Empty)
+                .collect();
+            let open = window.iter().all(|&(r, c)| self.get(r, c) != piece.opponent());
+            if open && !empty.is_empty() && empty.len() <= 2 {
+                let second = if empty.len() == 2 {
+                    empty[1]
+                } else {
+                    // any other empty cell will do
+                    match self.other_empty_cell(empty[0]) {
+                        Some(cell) => cell,
+                        None => return Some(Turn::single(empty[0].0, empty[0].1)),
+                    }
+                };
+                return Some(Turn::pair(empty[0], second))
+            }
+        }
+        None
+    }
+
+    fn other_empty_cell(&self, cell:(usize, usize)) -> Option<(usize, usize)> {
+        (0..N)
+            .flat_map(|r| (0..N).map(move |c| (r, c)))
+            .find(|&other| other != cell && self.get(other.0, other.1) == Piece::Empty)
+    }
+
+    // the empty cells, without duplicates, of every window holding at
+    // least min_stones of piece and none of the opponent's
+    fn open_cells(&self, piece:Piece, min_stones:usize) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for window in self.six_windows() {
+            let own = window.iter().filter(|&&(r, c)| self.get(r, c) == piece).count();
+            let open = window.iter().all(|&(r, c)| self.get(r, c) != piece.opponent());
+            if open && own >= min_stones {
+                for &(r, c) in window.iter() {
+                    if self.get(r, c) == Piece::Empty && !cells.contains(&(r, c)) {
+                        cells.push((r, c));
+                    }
+                }
+            }
+        }
+        cells.sort();
+        cells
+    }
+
+    /**
+     * Whether a stone of piece at row, col would make live fours (two
+     * threats each) in two or more of the lines through it. The opponent
+     * can't block both with a single turn.
+     */
+    pub fn is_double_four(&self, row:usize, col:usize, piece:Piece) -> bool {
+        if self.get(row, col) != Piece::Empty {
+            return false
+        }
+        let mut board = *self;
+        board.set(row, col, piece);
+        let fours = board.lines_through(row, col).iter()
+            .filter(|line| line.threats(piece) >= 2)
+            .count();
+        fours >= 2
+    }
+
+    /**
+     * Whether to_move's turn leaves it threats which no two stones of the
+     * opponent's can all block, as when each stone of the turn makes a
+     * live four in a different line. A turn which completes a six already
+     * wins, so isn't counted as a fork, nor is a turn onto occupied cells.
+     */
+    pub fn turn_creates_fork(&self, turn:&Turn, to_move:Piece) -> bool {
+        assert!(to_move != Piece::Empty);
+        if turn.cells().iter().any(|&(r, c)| self.get(r, c) != Piece::Empty) {
+            return false
+        }
+        let mut after = *self;
+        after.make_move(turn, to_move);
+        after.winner().is_none() && after.unblockable(&after.find_threats(to_move))
+    }
+
+    /**
+     * Whether to_move's turn lets the opponent complete a six with its
+     * reply, as when it leaves one of the opponent's threats unblocked.
+     * A turn which wins the game itself is never a blunder.
+     */
+    pub fn turn_is_blunder(&self, turn:&Turn, to_move:Piece) -> bool {
+        assert!(to_move != Piece::Empty);
+        let mut after = *self;
+        after.make_move(turn, to_move);
+        after.winner().is_none() && after.winning_turn(to_move.opponent()).is_some()
+    }
+
+    /**
+     * A rough measure of how hard the position is for to_move to play:
+     * ten for each threat either side has, one for every ten turns within
+     * a cell of the stones (see nearby_turns), and fifty more if to_move
+     * has a short forced win to find (find_vcf within two turns). A game
+     * already decided without search (see trivial_result), such as one
+     * to_move can win at once, scores zero.
+     */
+    pub fn complexity(&self, to_move:Piece) -> u32 {
+        if self.trivial_result(to_move).is_some() {
+            return 0
+        }
+        let threats = self.threat_count(Piece::Black) + self.threat_count(Piece::White);
+        let branching = self.nearby_turns(to_move, 1).count();
+        let forced = if self.find_vcf(to_move, 2).is_some() { 50 } else { 0 };
+        (10 * threats + branching / 10 + forced) as u32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Outcome;
+    use super::Threats;
+    use super::TurnRole;
+    use board::StandardBoard as Board;
+    use board::Line;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+    use board::WIN_LENGTH;
+    use turn::Turn;
+
+    #[test]
+    fn test_line_threats() {
+        assert_eq!(Line::of("--XXXX--").threats(Piece::Black), 2);
+        assert_eq!(Line::of("-XXXXX-").threats(Piece::Black), 2);
+        assert_eq!(Line::of("OXXXXX-").threats(Piece::Black), 1);
+        assert_eq!(Line::of("OXXXX--").threats(Piece::Black), 1);
+        assert_eq!(Line::of("OXXXXO").threats(Piece::Black), 0);
+        assert_eq!(Line::of("XX-XX-").threats(Piece::Black), 1);
+        assert_eq!(Line::of("--XXX--").threats(Piece::Black), 0);
+        assert_eq!(Line::of("--XXXX--").threats(Piece::White), 0);
+    }
+
+    #[test]
+    fn test_line_count_threats() {
+        let count = |s:&str| Line::of(s).count_threats(Piece::Black);
+        assert_eq!(count("-XXXXX-"), Threats { fives: 1, live_fours: 0 });
+        assert_eq!(count("XXXXX-"), Threats { fives: 1, live_fours: 0 });
+        assert_eq!(count("OXXXXXO"), Threats { fives: 0, live_fours: 0 });
+        assert_eq!(count("--XXXX--"), Threats { fives: 0, live_fours: 1 });
+        // only one window can be filled
+        assert_eq!(count("O-XXXX-O"), Threats { fives: 0, live_fours: 0 });
+        assert_eq!(count("OXXXX--"), Threats { fives: 0, live_fours: 0 });
+        assert_eq!(count("XXXXXX"), Threats { fives: 0, live_fours: 0 });
+        assert_eq!(count("-XXXXX--XXXX--"), Threats { fives: 1, live_fours: 1 });
+        assert_eq!(Line::of("--OOOO--").count_threats(Piece::White).live_fours, 1);
+    }
+
+    #[test]
+    fn test_is_double_four() {
+        let mut b = Board::empty();
+        for i in 1..4 {
+            b.set(CENTER, CENTER - i, Piece::Black);
+            b.set(CENTER - i, CENTER, Piece::Black);
+        }
+        assert!(b.is_double_four(CENTER, CENTER, Piece::Black));
+        assert!(!b.is_double_four(CENTER, CENTER, Piece::White));
+        assert!(!b.is_double_four(CENTER, CENTER - 4, Piece::Black));
+
+        b.set(CENTER, CENTER, Piece::White);
+        assert!(!b.is_double_four(CENTER, CENTER, Piece::Black));
+    }
+
+    #[test]
+    fn test_turn_creates_fork() {
+        let mut b = Board::empty();
+        for c in 3..6 {
+            b.set(3, c, Piece::Black);
+            b.set(15, c, Piece::Black);
+        }
+        // a live four in each line takes four stones to block
+        let fork = Turn::pair((3, 6), (15, 6));
+        assert!(b.turn_creates_fork(&fork, Piece::Black));
+        assert!(!b.turn_creates_fork(&fork, Piece::White));
+        assert!(!b.turn_creates_fork(&Turn::pair((3, 6), (0, 0)), Piece::Black));
+        // a live five is blocked at both ends
+        assert!(!b.turn_creates_fork(&Turn::pair((3, 6), (3, 7)), Piece::Black));
+
+        // completing the six wins outright
+        b.set(3, 6, Piece::Black);
+        b.set(3, 7, Piece::Black);
+        assert!(!b.turn_creates_fork(&Turn::pair((3, 8), (15, 6)), Piece::Black));
+        assert!(!b.turn_creates_fork(&fork, Piece::Black));
+
+        // with the first line boxed in only the other four is left
+        b.set(3, 6, Piece::Empty);
+        b.set(3, 7, Piece::White);
+        b.set(3, 2, Piece::White);
+        assert!(!b.turn_creates_fork(&fork, Piece::Black));
+    }
+
+    #[test]
+    fn test_turn_is_blunder() {
+        let mut b = Board::empty();
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::White);
+        }
+        b.set(0, 0, Piece::Black);
+        let ignore = Turn::pair((0, 1), (0, 2));
+        let half = Turn::pair((CENTER, CENTER - 3), (0, 1));
+        let block = Turn::pair((CENTER, CENTER - 3), (CENTER, CENTER + 2));
+        assert!(b.turn_is_blunder(&ignore, Piece::Black));
+        assert!(b.turn_is_blunder(&half, Piece::Black));
+        assert!(!b.turn_is_blunder(&block, Piece::Black));
+
+        // winning first makes the open four irrelevant
+        for c in 1..5 {
+            b.set(0, c, Piece::Black);
+        }
+        assert!(!b.turn_is_blunder(&Turn::pair((0, 5), (1, 1)), Piece::Black));
+    }
+
+    #[test]
+    fn test_threat_count() {
+        let mut b = Board::empty();
+        assert_eq!(b.threat_count(Piece::Black), 0);
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.threat_count(Piece::Black), 2);
+        b.set(CENTER, 7, Piece::White);
+        assert_eq!(b.threat_count(Piece::Black), 1);
+    }
+
+    #[test]
+    fn test_threats_by_direction() {
+        let mut b = Board::empty();
+        assert_eq!(b.threats_by_direction(Piece::Black), [0; 4]);
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.threats_by_direction(Piece::Black), [2, 0, 0, 0]);
+        for i in 0..4 {
+            b.set(i, BOARD_SIZE - 1 - i, Piece::Black);
+            b.set(12 + i, 12 + i, Piece::Black);
+        }
+        b.set(11, 11, Piece::White);
+        let counts = b.threats_by_direction(Piece::Black);
+        assert_eq!(counts, [2, 0, 1, 1]);
+        assert_eq!(counts.iter().sum::<usize>(), b.threat_count(Piece::Black));
+    }
+
+    #[test]
+    fn test_blocks_for() {
+        let mut b = Board::empty();
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        let threats = b.find_threats(Piece::Black);
+        assert_eq!(threats.len(), 3);
+        assert!(b.find_threats(Piece::White).is_empty());
+
+        // the middle window of --XXXX-- is blocked at either end
+        let live = threats.iter().find(|t| t.cells()[0] == (CENTER, CENTER - 3)).unwrap();
+        assert_eq!(live.piece(), Piece::Black);
+        assert_eq!(b.blocks_for(live), vec![(CENTER, CENTER - 3), (CENTER, CENTER + 2)]);
+        b.set(CENTER, CENTER + 2, Piece::White);
+        assert_eq!(b.find_threats(Piece::Black).len(), 1);
+    }
+
+    #[test]
+    fn test_gap_threats() {
+        let mut b = Board::empty();
+        for &c in [3, 4, 5, 7, 8].iter() {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.gap_threats(Piece::Black), vec![(CENTER, 6)]);
+        assert!(b.gap_threats(Piece::White).is_empty());
+
+        // a blocked gap is no threat, and a four leaves two cells empty
+        b.set(CENTER, 6, Piece::White);
+        assert!(b.gap_threats(Piece::Black).is_empty());
+        for c in 0..4 {
+            b.set(0, c, Piece::Black);
+        }
+        assert!(b.gap_threats(Piece::Black).is_empty());
+    }
+
+    #[test]
+    fn test_open_threes() {
+        let mut b = Board::empty();
+        for c in 5..8 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.open_threes(Piece::Black, false), vec![[(CENTER, 5), (CENTER, 6), (CENTER, 7)]]);
+        assert!(b.open_threes(Piece::White, false).is_empty());
+
+        // OXXX- is closed at one end
+        b.set(CENTER, 4, Piece::White);
+        assert!(b.open_threes(Piece::Black, false).is_empty());
+        // and -XXX-O too short to win in, against the edge
+        let mut edge = Board::empty();
+        for c in 1..4 {
+            edge.set(0, c, Piece::Black);
+        }
+        edge.set(0, 5, Piece::White);
+        assert!(edge.open_threes(Piece::Black, false).is_empty());
+
+        let mut gapped = Board::empty();
+        for &c in [5, 7, 8].iter() {
+            gapped.set(3, c, Piece::White);
+        }
+        assert!(gapped.open_threes(Piece::White, false).is_empty());
+        assert_eq!(gapped.open_threes(Piece::White, true), vec![[(3, 5), (3, 7), (3, 8)]]);
+    }
+
+    #[test]
+    fn test_jointly_blockable() {
+        let mut b = Board::empty();
+        assert!(b.jointly_blockable(Piece::Black).is_empty());
+        // fives capped on the left, each with one open end
+        for &row in [3, 9].iter() {
+            b.set(row, 0, Piece::White);
+            for c in 1..6 {
+                b.set(row, c, Piece::Black);
+            }
+        }
+        assert_eq!(b.jointly_blockable(Piece::Black), vec![((3, 6), (9, 6))]);
+        assert!(b.jointly_blockable(Piece::White).is_empty());
+
+        // a live four alone takes both stones
+        let mut four = Board::empty();
+        for c in 3..7 {
+            four.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(four.jointly_blockable(Piece::Black), vec![
+            ((CENTER, 1), (CENTER, 7)),
+            ((CENTER, 2), (CENTER, 7)),
+            ((CENTER, 2), (CENTER, 8)),
+        ]);
+
+        b.set(15, 0, Piece::White);
+        for c in 1..6 {
+            b.set(15, c, Piece::Black);
+        }
+        assert!(b.jointly_blockable(Piece::Black).is_empty());
+        assert!(b.is_lost(Piece::White));
+    }
+
+    #[test]
+    fn test_is_lost() {
+        let mut b = Board::empty();
+        assert!(!b.is_lost(Piece::Black));
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::White);
+        }
+        assert!(!b.is_lost(Piece::Black));
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER - 4, c, Piece::White);
+        }
+        assert!(b.is_lost(Piece::Black));
+        assert!(!b.is_lost(Piece::White));
+
+        // unless Black can win first
+        for c in 0..4 {
+            b.set(0, c, Piece::Black);
+        }
+        assert!(!b.is_lost(Piece::Black));
+    }
+
+    #[test]
+    fn test_requires_response() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER + 1, CENTER, Piece::White);
+        assert!(!b.requires_response(Piece::Black));
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER - 3, c, Piece::White);
+        }
+        assert!(b.requires_response(Piece::Black));
+        assert!(!b.requires_response(Piece::White));
+
+        // a win of its own needs no defense
+        for c in 0..4 {
+            b.set(0, c, Piece::Black);
+        }
+        assert!(!b.requires_response(Piece::Black));
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(BOARD_SIZE - 1, 0, Piece::White);
+        assert!(b.is_quiet(Piece::Black));
+        assert!(b.is_quiet(Piece::White));
+
+        // Black's pair could become a threat on Black's next turn,
+        // whoever is to move
+        b.set(CENTER, CENTER + 1, Piece::Black);
+        assert!(!b.is_quiet(Piece::Black));
+        assert!(!b.is_quiet(Piece::White));
+
+        b.set(CENTER, CENTER + 2, Piece::Black);
+        b.set(CENTER, CENTER + 3, Piece::Black);
+        assert!(!b.is_quiet(Piece::White));
+    }
+
+    // a full board without a six: the colors alternate along the rows,
+    // and every two cells up the columns
+    fn drawn_board() -> Board {
+        let full:Vec<Piece> = (0..BOARD_SIZE * BOARD_SIZE).map(|i| {
+            let (r, c) = (i / BOARD_SIZE, i % BOARD_SIZE);
+            if (r / 2 + c) % 2 == 0 { Piece::Black } else { Piece::White }
+        }).collect();
+        Board::from_vec(&full).unwrap()
+    }
+
+    #[test]
+    fn test_trivial_result() {
+        let mut b = Board::empty();
+        assert_eq!(b.trivial_result(Piece::Black), None);
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::White);
+            b.set(CENTER - 4, c, Piece::White);
+        }
+        assert_eq!(b.trivial_result(Piece::White), Some(Outcome::Winner(Piece::White)));
+        assert_eq!(b.trivial_result(Piece::Black), Some(Outcome::Winner(Piece::White)));
+
+        b.set(CENTER, CENTER - 3, Piece::Black);
+        b.set(CENTER, CENTER + 2, Piece::Black);
+        assert_eq!(b.trivial_result(Piece::Black), None);
+
+        for c in 0..WIN_LENGTH {
+            b.set(0, c, Piece::Black);
+        }
+        assert_eq!(b.trivial_result(Piece::White), Some(Outcome::Winner(Piece::Black)));
+
+        let full = drawn_board();
+        assert_eq!(full.winner(), None);
+        assert_eq!(full.trivial_result(Piece::Black), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn test_outcome() {
+        let mut b = Board::empty();
+        assert_eq!(b.outcome(), None);
+        for c in 0..WIN_LENGTH - 1 {
+            b.set(CENTER, c, Piece::White);
+        }
+        // a move from winning isn't over
+        assert_eq!(b.outcome(), None);
+        b.set(CENTER, WIN_LENGTH - 1, Piece::White);
+        assert_eq!(b.outcome(), Some(Outcome::Winner(Piece::White)));
+
+        let mut full = drawn_board();
+        assert_eq!(full.outcome(), Some(Outcome::Draw));
+        full.set(0, 0, Piece::Empty);
+        assert_eq!(full.outcome(), None);
+    }
+
+    #[test]
+    fn test_drawing_cells() {
+        // White everywhere but a stretch of seven cells, five of them Black
+        let mut cells = vec![Piece::White; BOARD_SIZE * BOARD_SIZE];
+        for c in 0..7 {
+            cells[CENTER * BOARD_SIZE + c] = if c < 5 { Piece::Black } else { Piece::Empty };
+        }
+        let mut b = Board::from_vec(&cells).unwrap();
+        assert_eq!(b.drawing_cells(Piece::White), Some(vec![(CENTER, 5)]));
+
+        b.set(CENTER, 5, Piece::White);
+        assert_eq!(b.drawing_cells(Piece::White), Some(vec![]));
+        b.set(CENTER, 5, Piece::Black);
+        assert_eq!(b.drawing_cells(Piece::White), None);
+
+        let mut open = Board::empty();
+        open.set(CENTER, CENTER, Piece::Black);
+        let blocks = open.drawing_cells(Piece::White).unwrap();
+        for window in open.six_windows() {
+            assert!(window.iter().any(|cell| blocks.contains(cell)));
+        }
+    }
+
+    #[test]
+    fn test_turn_synergy() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER, CENTER + 1, Piece::Black);
+        let together = Turn::pair((CENTER, CENTER + 2), (CENTER, CENTER + 3));
+        let apart = Turn::pair((CENTER, CENTER + 2), (0, 0));
+        assert_eq!(b.turn_synergy(&together, Piece::Black), 2);
+        assert_eq!(b.turn_synergy(&apart, Piece::Black), 0);
+        assert!(b.turn_synergy(&together, Piece::Black) > b.turn_synergy(&apart, Piece::Black));
+        assert_eq!(b.turn_synergy(&Turn::single(CENTER, CENTER + 2), Piece::Black), 0);
+    }
+
+    #[test]
+    fn test_best_defense() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.best_defense(Piece::Black), None);
+        for c in CENTER - 2..CENTER + 2 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        let (r, c) = b.best_defense(Piece::Black).unwrap();
+        assert_eq!((r, c), (CENTER, CENTER + 2));
+        b.set(r, c, Piece::White);
+        assert_eq!(b.threat_count(Piece::Black), 1);
+        assert_eq!(b.best_defense(Piece::White), None);
+    }
+
+    #[test]
+    fn test_defensive_turns() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(b.defensive_turns(Piece::White).is_empty());
+
+        for c in CENTER + 1..CENTER + 5 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        b.set(CENTER, CENTER - 1, Piece::White);
+        b.set(3, 3, Piece::White);
+        b.set(3, 4, Piece::White);
+        let threats = b.find_threats(Piece::Black);
+        let turns = b.defensive_turns(Piece::White);
+        assert!(!turns.is_empty());
+        for (i, turn) in turns.iter().enumerate() {
+            assert!(turn.cells().iter().any(|cell| threats.iter().any(|t| b.blocks_for(t).contains(cell))));
+            let mut after = b;
+            after.make_move(turn, Piece::White);
+            assert!(after.threat_count(Piece::Black) < b.threat_count(Piece::Black));
+            assert!(!turns[..i].contains(turn));
+        }
+        // the second stone may extend White's own pair instead
+        assert!(turns.iter().any(|turn| turn.cells().contains(&(3, 5))));
+    }
+
+    #[test]
+    fn test_defensive_turns_single_block() {
+        // a five with only one empty end, and nothing else on the board
+        let mut b = Board::empty();
+        b.set(CENTER, 0, Piece::White);
+        b.set(CENTER, 7, Piece::White);
+        for c in 1..6 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.threat_count(Piece::Black), 1);
+        let turns = b.defensive_turns(Piece::White);
+        assert_eq!(turns.len(), 1);
+        assert!(turns[0].cells().contains(&(CENTER, 6)));
+        let mut after = b;
+        after.make_move(&turns[0], Piece::White);
+        assert_eq!(after.threat_count(Piece::Black), 0);
+    }
+
+    #[test]
+    fn test_find_forced_win_immediate() {
+        let mut b = Board::empty();
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        let win = b.find_forced_win(Piece::Black, 1).unwrap();
+        assert_eq!(win.len(), 1);
+        b.make_move(&win[0], Piece::Black);
+        assert_eq!(b.winner(), Some(Piece::Black));
+    }
+
+    #[test]
+    fn test_find_forced_win_two_moves() {
+        // two open threes: extending both makes two live fours, which
+        // need four stones to block
+        let mut b = Board::empty();
+        for c in 7..10 {
+            b.set(5, c, Piece::Black);
+            b.set(12, c, Piece::Black);
+        }
+        b.set(CENTER, CENTER, Piece::White);
+        b.set(0, 0, Piece::White);
+        assert!(b.find_forced_win(Piece::Black, 1).is_none());
+
+        let win = b.find_forced_win(Piece::Black, 2).unwrap();
+        assert_eq!(win.len(), 3);
+        let mut after = b;
+        after.make_move(&win[0], Piece::Black);
+        assert!(after.threat_count(Piece::Black) >= 3);
+        after.make_move(&win[1], Piece::White);
+        after.make_move(&win[2], Piece::Black);
+        assert_eq!(after.winner(), Some(Piece::Black));
+    }
+
+    #[test]
+    fn test_find_forced_win_none() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert!(b.find_forced_win(Piece::Black, 2).is_none());
+
+        // White's four must be answered first
+        for c in 7..10 {
+            b.set(5, c, Piece::Black);
+            b.set(12, c, Piece::Black);
+        }
+        for c in 0..4 {
+            b.set(0, c, Piece::White);
+        }
+        assert!(b.find_forced_win(Piece::Black, 2).is_none());
+    }
+
+    // Black has just made a closed four along row 2 and another down
+    // column 8, both blocked at (2, 8), with two open threes to follow
+    // up with; White has an open three of its own along row 0
+    fn free_stone_board() -> Board {
+        let mut b = Board::empty();
+        b.set(2, 2, Piece::White);
+        b.set(7, 8, Piece::White);
+        for i in 4..8 {
+            b.set(2, i, Piece::Black);
+            b.set(i - 1, 8, Piece::Black);
+        }
+        for i in 8..11 {
+            b.set(12, i, Piece::Black);
+            b.set(i, 15, Piece::Black);
+        }
+        for c in 12..15 {
+            b.set(0, c, Piece::White);
+        }
+        b
+    }
+
+    #[test]
+    fn test_refute_with_free_stone() {
+        // blocking at (2, 8) frees White's other stone, which makes a four
+        let b = free_stone_board();
+        assert_eq!(b.threat_count(Piece::Black), 2);
+        assert!(b.refute_all_defenses(Piece::Black, 2).is_none());
+        // anywhere near the fours instead, and the threes still win
+        let mut after = b;
+        after.make_move(&Turn::pair((2, 8), (2, 9)), Piece::White);
+        assert!(after.find_forced_win(Piece::Black, 2).is_some());
+    }
+
+    #[test]
+    fn test_find_vcf() {
+        // an open three, and two pairs crossing at (8, 12): a four from
+        // the three forces both stones, leaving two open threes
+        let mut b = Board::empty();
+        for c in 7..10 {
+            b.set(3, c, Piece::Black);
+        }
+        for i in 1..3 {
+            b.set(8, 12 - i, Piece::Black);
+            b.set(8 + i, 12, Piece::Black);
+        }
+        b.set(CENTER, CENTER, Piece::White);
+        b.set(0, 0, Piece::White);
+        assert!(b.find_vcf(Piece::Black, 2).is_none());
+
+        let line = b.find_vcf(Piece::Black, 3).unwrap();
+        assert_eq!(line.len(), 5);
+        let mut after = b;
+        for (i, turn) in line.iter().enumerate() {
+            let piece = if i % 2 == 0 { Piece::Black } else { Piece::White };
+            after.make_move(turn, piece);
+            if i % 2 == 0 && i + 1 < line.len() {
+                assert!(after.threat_count(Piece::Black) > 0);
+            }
+        }
+        assert_eq!(after.winner(), Some(Piece::Black));
+
+        // White's own four comes first
+        for c in 0..4 {
+            b.set(0, c, Piece::White);
+        }
+        assert!(b.find_vcf(Piece::Black, 3).is_none());
+        assert!(Board::empty().find_vcf(Piece::Black, 2).is_none());
+    }
+
+    #[test]
+    fn test_find_vcf_free_stone() {
+        // as for find_forced_win, White's free stone makes a four
+        let b = free_stone_board();
+        let threats = b.find_threats(Piece::Black);
+        assert!(b.refute_blocks(Piece::Black, &threats, 2).is_none());
+        // spent on one of the threes, Black fours the other
+        let mut after = b;
+        after.make_move(&Turn::pair((2, 8), (12, 11)), Piece::White);
+        assert!(after.find_vcf(Piece::Black, 2).is_some());
+    }
+
+    #[test]
+    fn test_complexity() {
+        // Black to move can complete its open four
+        let mut trivial = Board::empty();
+        for c in 7..11 {
+            trivial.set(CENTER, c, Piece::Black);
+        }
+        trivial.set(CENTER + 1, CENTER, Piece::White);
+        assert_eq!(trivial.complexity(Piece::Black), 0);
+
+        // White's four has to be blocked while both sides' threes cross
+        let mut tangled = Board::empty();
+        for c in 3..7 {
+            tangled.set(5, c, Piece::White);
+        }
+        tangled.set(5, 2, Piece::Black);
+        for i in 0..3 {
+            tangled.set(CENTER, 7 + i, Piece::Black);
+            tangled.set(6 + i, CENTER + 3, Piece::Black);
+            tangled.set(CENTER + 2, 6 + i, Piece::White);
+            tangled.set(13 + i, 2 + i, Piece::White);
+        }
+        assert!(tangled.trivial_result(Piece::Black).is_none());
+        assert!(trivial.complexity(Piece::Black) < tangled.complexity(Piece::Black));
+
+        let mut opening = Board::empty();
+        opening.set(CENTER, CENTER, Piece::Black);
+        assert!(opening.complexity(Piece::White) < tangled.complexity(Piece::Black));
+    }
+
+    #[test]
+    fn test_new_threats() {
+        let mut b = Board::empty();
+        for c in 5..8 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        assert_eq!(b.new_threats(CENTER, 8, Piece::Black), 2);
+        assert_eq!(b.new_threats(CENTER, 0, Piece::Black), 0);
+        assert_eq!(b.new_threats(CENTER, 8, Piece::White), 0);
+        assert_eq!(b.new_threats(CENTER, 5, Piece::Black), 0);
+
+        b.set(CENTER, 8, Piece::Black);
+        assert_eq!(b.new_threats(CENTER, 9, Piece::Black), 0);
+        b.set(CENTER, 4, Piece::White);
+        assert_eq!(b.new_threats(CENTER, 9, Piece::Black), 0);
+        assert_eq!(b.new_threats(CENTER, 10, Piece::Black), 0);
+    }
+
+    #[test]
+    fn test_classify_turn() {
+        let mut b = Board::empty();
+        for c in 3..7 {
+            b.set(CENTER, c, Piece::Black);
+        }
+        for c in 3..6 {
+            b.set(CENTER - 1, c, Piece::White);
+        }
+        let block = (CENTER, 7);
+        let extend = (CENTER - 1, 6);
+        assert_eq!(b.classify_turn(&Turn::pair(block, extend), Piece::White), TurnRole::Both);
+        assert_eq!(b.classify_turn(&Turn::pair(block, (0, 0)), Piece::White), TurnRole::Defense);
+        assert_eq!(b.classify_turn(&Turn::pair(extend, (0, 0)), Piece::White), TurnRole::Attack);
+        assert_eq!(b.classify_turn(&Turn::pair((0, 0), (0, 18)), Piece::White), TurnRole::Neutral);
+    }
+}
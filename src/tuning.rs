@@ -0,0 +1,45 @@
+use board::Board;
+use board::Piece;
+
+/**
+ * The fraction of boards on which two evaluation functions agree about
+ * which side is ahead, i.e. return scores of the same sign when asked
+ * for Black's advantage. An empty set of boards counts as agreement.
+ */
+pub fn eval_agreement(
+        boards:&[Board],
+        a:&dyn Fn(&Board, Piece) -> i32,
+        b:&dyn Fn(&Board, Piece) -> i32) -> f64 {
+    if boards.is_empty() {
+        return 1.0
+    }
+    let agreed = boards.iter()
+        .filter(|board| a(board, Piece::Black).signum() == b(board, Piece::Black).signum())
+        .count();
+    agreed as f64 / boards.len() as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::eval_agreement;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::CENTER;
+
+    fn stones(board:&Board, piece:Piece) -> i32 {
+        let lead = board.count(Piece::Black) as i32 - board.count(Piece::White) as i32;
+        if piece == Piece::Black { lead } else { -lead }
+    }
+
+    #[test]
+    fn test_eval_agreement() {
+        let mut boards = vec![Board::empty(); 4];
+        boards[1].set(CENTER, CENTER, Piece::Black);
+        boards[2].set(CENTER, CENTER, Piece::White);
+        boards[3].set(0, 0, Piece::White);
+
+        assert_eq!(eval_agreement(&boards, &stones, &stones), 1.0);
+        assert_eq!(eval_agreement(&boards, &stones, &|_, _| 0), 0.25);
+        assert_eq!(eval_agreement(&[], &stones, &|_, _| 0), 1.0);
+    }
+}
@@ -0,0 +1,300 @@
+use std::iter;
+
+use board::Board;
+use board::Piece;
+use game::TurnError;
+
+/**
+ * The stones placed in a single turn: one for Black's opening turn,
+ * and two for every turn after that.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct Turn {
+    size : usize,
+    cells : [(usize, usize); 2],
+}
+
+/**
+ * What make_move overwrote, so that unmake_move can put it back.
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct Undo {
+    turn : Turn,
+    previous : [Piece; 2],
+}
+
+/**
+ * A turn together with the color placing its stones.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Move {
+    piece : Piece,
+    turn : Turn,
+}
+
+/**
+ * A turn with the time each stone was placed, in milliseconds from any
+ * fixed start such as the beginning of the game, where it is known. The
+ * times are only for presentation, e.g. to animate a replay, and play no
+ * part in applying the turn.
+ */
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TimedTurn {
+    turn : Turn,
+    // placed_at[i] is for turn.cells()[i]
+    placed_at : [Option<u64>; 2],
+}
+
+impl TimedTurn {
+    pub fn new(turn:Turn, placed_at:[Option<u64>; 2]) -> TimedTurn {
+        TimedTurn { turn, placed_at }
+    }
+
+    // a turn whose times aren't known
+    pub fn untimed(turn:Turn) -> TimedTurn {
+        TimedTurn { turn, placed_at: [None, None] }
+    }
+
+    pub fn turn(&self) -> &Turn {
+        &self.turn
+    }
+
+    pub fn placed_at(&self) -> &[Option<u64>] {
+        &self.placed_at[..self.turn.size()]
+    }
+
+    /**
+     * The turn's cells in the order the stones were placed: by time where
+     * both are known, and otherwise in the order the turn lists them.
+     */
+    pub fn placement_order(&self) -> Vec<(usize, usize)> {
+        let mut cells:Vec<((usize, usize), Option<u64>)> = self.turn.cells().iter().cloned()
+            .zip(self.placed_at().iter().cloned())
+            .collect();
+        if cells.iter().all(|&(_, time)| time.is_some()) {
+            cells.sort_by_key(|&(_, time)| time);
+        }
+        cells.into_iter().map(|(cell, _)| cell).collect()
+    }
+}
+
+impl Move {
+    pub fn new(piece:Piece, turn:Turn) -> Move {
+        assert!(piece != Piece::Empty);
+        Move { piece, turn }
+    }
+
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
+    pub fn turn(&self) -> &Turn {
+        &self.turn
+    }
+}
+
+impl Turn {
+    pub fn single(row:usize, col:usize) -> Turn {
+        Turn { size: 1, cells: [(row, col), (row, col)] }
+    }
+
+    pub fn pair(a:(usize, usize), b:(usize, usize)) -> Turn {
+        Turn { size: 2, cells: [a, b] }
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells[..self.size]
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /**
+     * Check that the turn could be played on board by either color: its
+     * cells are on the board and empty, and a two stone turn's cells
+     * are different. Which turn of the game it is isn't considered.
+     */
+    pub fn validate<const N: usize>(&self, board:&Board<N>) -> Result<(), TurnError> {
+        for &(r, c) in self.cells() {
+            if r >= N || c >= N {
+                return Err(TurnError::OutOfBounds(r, c))
+            }
+            if board.get(r, c) != Piece::Empty {
+                return Err(TurnError::Occupied(r, c))
+            }
+        }
+        if self.size == 2 && self.cells[0] == self.cells[1] {
+            return Err(TurnError::SameCell(self.cells[0].0, self.cells[0].1))
+        }
+        Ok(())
+    }
+}
+
+impl Undo {
+    pub fn turn(&self) -> &Turn {
+        &self.turn
+    }
+}
+
+impl<const N: usize> Board<N> {
+    /**
+     * Place the stones of a turn, recording only the cells changed so
+     * that unmake_move can restore them without copying the board.
+     */
+    pub fn make_move(&mut self, turn:&Turn, piece:Piece) -> Undo {
+        let mut previous = [Piece::Empty; 2];
+        for (i, &(r, c)) in turn.cells().iter().enumerate() {
+            previous[i] = self.set(r, c, piece);
+        }
+        Undo { turn: *turn, previous }
+    }
+
+    pub fn unmake_move(&mut self, undo:Undo) {
+        for (i, &(r, c)) in undo.turn.cells().iter().enumerate().rev() {
+            self.set(r, c, undo.previous[i]);
+        }
+    }
+
+    /**
+     * Every turn to_move could play: a single stone on any empty cell for
+     * Black's opening turn on an empty board, or when only one empty cell
+     * is left, and otherwise every pair of empty cells, each pair once
+     * with its cells in row-major order.
+     */
+    pub fn legal_turns(&self, to_move:Piece) -> impl Iterator<Item = Turn> {
+        assert!(to_move != Piece::Empty);
+        turns_among(self.empty_cells().collect(), self.single_stone_turn(to_move))
+    }
+
+    /**
+     * The legal_turns whose stones are all within Chebyshev distance
+     * radius of a stone already played, or of the center on an empty
+     * board: the turns worth considering in most positions.
+     */
+    pub fn nearby_turns(&self, to_move:Piece, radius:usize) -> impl Iterator<Item = Turn> {
+        let cells = if self.empty_count() == N * N {
+            self.center_region(radius).map(|(r, c, _)| (r, c)).collect()
+        } else {
+            self.cells_near_stones(radius).collect()
+        };
+        turns_among(cells, self.single_stone_turn(to_move))
+    }
+
+    // whether to_move's turn is a single stone, not a pair
+    fn single_stone_turn(&self, to_move:Piece) -> bool {
+        self.empty_count() == 1 || (to_move == Piece::Black && self.empty_count() == N * N)
+    }
+}
+
+// a single stone on each of the cells, or each pair of them in order
+fn turns_among(cells:Vec<(usize, usize)>, single:bool) -> impl Iterator<Item = Turn> {
+    // the indices of the last turn's cells in cells
+    let (mut i, mut j) = (0, 0);
+    iter::from_fn(move || {
+        if single {
+            let &(r, c) = cells.get(i)?;
+            i += 1;
+            return Some(Turn::single(r, c))
+        }
+        j += 1;
+        if j >= cells.len() {
+            i += 1;
+            j = i + 1;
+        }
+        if j >= cells.len() {
+            return None
+        }
+        Some(Turn::pair(cells[i], cells[j]))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Turn;
+    use board::StandardBoard as Board;
+    use game::TurnError;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_turn_cells() {
+        assert_eq!(Turn::single(CENTER, CENTER).cells(), &[(CENTER, CENTER)]);
+        assert_eq!(Turn::pair((1, 2), (3, 4)).cells(), &[(1, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(Turn::pair((0, 0), (0, 1)).validate(&b), Ok(()));
+        assert_eq!(Turn::single(0, 0).validate(&b), Ok(()));
+        assert_eq!(Turn::pair((0, 0), (0, 0)).validate(&b), Err(TurnError::SameCell(0, 0)));
+        assert_eq!(Turn::pair((0, 0), (CENTER, CENTER)).validate(&b), Err(TurnError::Occupied(CENTER, CENTER)));
+        assert_eq!(Turn::pair((BOARD_SIZE, 0), (0, 0)).validate(&b), Err(TurnError::OutOfBounds(BOARD_SIZE, 0)));
+    }
+
+    #[test]
+    fn test_make_unmake() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER, CENTER+1, Piece::White);
+        let before = b;
+
+        let undo = b.make_move(&Turn::pair((0, 0), (CENTER, CENTER+1)), Piece::Black);
+        assert_eq!(b.get(0, 0), Piece::Black);
+        assert_eq!(b.get(CENTER, CENTER+1), Piece::Black);
+        b.unmake_move(undo);
+        assert!(b == before);
+        assert!(b.verify_hash());
+
+        let undo = b.make_move(&Turn::single(1, 1), Piece::White);
+        b.unmake_move(undo);
+        assert!(b == before);
+        assert!(b.verify_hash());
+    }
+
+    #[test]
+    fn test_legal_turns() {
+        let mut b = Board::empty();
+        let cells = BOARD_SIZE * BOARD_SIZE;
+        assert_eq!(b.legal_turns(Piece::Black).count(), cells);
+        assert!(b.legal_turns(Piece::Black).all(|turn| turn.size() == 1));
+
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(CENTER, CENTER + 1, Piece::White);
+        b.set(0, 0, Piece::White);
+        let empty = cells - 3;
+        let turns:Vec<Turn> = b.legal_turns(Piece::Black).collect();
+        assert_eq!(turns.len(), empty * (empty - 1) / 2);
+        assert_eq!(turns[0], Turn::pair((0, 1), (0, 2)));
+        for turn in turns.iter().take(1000) {
+            let cells = turn.cells();
+            assert!(cells[0] < cells[1]);
+            assert!(cells.iter().all(|&(r, c)| b.get(r, c) == Piece::Empty));
+        }
+
+        let mut last = Board::from_vec(&[Piece::White; BOARD_SIZE * BOARD_SIZE]).unwrap();
+        last.set(3, 4, Piece::Empty);
+        assert_eq!(last.legal_turns(Piece::Black).collect::<Vec<_>>(), vec![Turn::single(3, 4)]);
+        last.set(3, 4, Piece::Black);
+        assert_eq!(last.legal_turns(Piece::White).count(), 0);
+    }
+
+    #[test]
+    fn test_nearby_turns() {
+        let b = Board::empty();
+        let opening:Vec<Turn> = b.nearby_turns(Piece::Black, 1).collect();
+        assert_eq!(opening.len(), 9);
+        assert!(opening.contains(&Turn::single(CENTER, CENTER)));
+
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        // three cells by the corner and eight round the center
+        assert_eq!(b.nearby_turns(Piece::White, 1).count(), 11 * 10 / 2);
+        assert!(b.nearby_turns(Piece::White, 1).all(|turn| b.legal_turns(Piece::White).any(|t| t == turn)));
+        assert_eq!(b.nearby_turns(Piece::White, 0).count(), 0);
+    }
+}
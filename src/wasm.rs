@@ -0,0 +1,104 @@
+use wasm_bindgen::prelude::*;
+
+use board::stone_color;
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+use search::greedy_move;
+
+/**
+ * A game for JavaScript callers, built with the `wasm` feature. Stones
+ * are played one at a time, and the color of each is determined by the
+ * Connect6 cadence: Black places one stone, then each player places two.
+ * Errors are returned as exceptions rather than panicking.
+ */
+#[wasm_bindgen]
+pub struct WasmGame {
+    board : Board,
+    stones : usize,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> WasmGame {
+        WasmGame { board: Board::empty(), stones: 0 }
+    }
+
+    /**
+     * Place the next stone at row, col, throwing if the cell is off the
+     * board or occupied, or the game is already over.
+     */
+    pub fn play(&mut self, row:usize, col:usize) -> Result<(), JsValue> {
+        self.try_play(row, col).map_err(JsValue::from_str)
+    }
+
+    /**
+     * Choose and play the next stone, returning its [row, col], or
+     * undefined if the game is over.
+     */
+    pub fn gen_move(&mut self) -> Option<Vec<u32>> {
+        if self.board.winner().is_some() {
+            return None
+        }
+        let (r, c) = greedy_move(&self.board, stone_color(self.stones))?;
+        self.try_play(r, c).ok()?;
+        Some(vec![r as u32, c as u32])
+    }
+
+    pub fn board_json(&self) -> String {
+        self.board.to_json()
+    }
+
+    // 0 if nobody has won, 1 for black and 2 for white
+    pub fn winner(&self) -> u8 {
+        match self.board.winner() {
+            None | Some(Piece::Empty) => 0,
+            Some(Piece::Black) => 1,
+            Some(Piece::White) => 2,
+        }
+    }
+}
+
+impl WasmGame {
+    fn try_play(&mut self, row:usize, col:usize) -> Result<(), &'static str> {
+        if row >= BOARD_SIZE || col >= BOARD_SIZE {
+            return Err("cell is off the board")
+        }
+        if self.board.get(row, col) != Piece::Empty {
+            return Err("cell is occupied")
+        }
+        if self.board.winner().is_some() {
+            return Err("the game is over")
+        }
+        self.board.set(row, col, stone_color(self.stones));
+        self.stones += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WasmGame;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_wasm_game() {
+        let mut g = WasmGame::new();
+        assert_eq!(g.try_play(CENTER, CENTER), Ok(()));
+        assert_eq!(g.try_play(CENTER, CENTER), Err("cell is occupied"));
+        assert_eq!(g.try_play(BOARD_SIZE, 0), Err("cell is off the board"));
+
+        let json = g.board_json();
+        assert_eq!(Board::from_json(&json).unwrap().get(CENTER, CENTER), Piece::Black);
+
+        let cell = g.gen_move().unwrap();
+        let board = Board::from_json(&g.board_json()).unwrap();
+        assert_eq!(board.get(cell[0] as usize, cell[1] as usize), Piece::White);
+        assert_eq!(g.winner(), 0);
+    }
+}
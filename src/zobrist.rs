@@ -0,0 +1,244 @@
+use board::Board;
+use board::Piece;
+use board::BOARD_SIZE;
+
+const CELLS : usize = BOARD_SIZE * BOARD_SIZE;
+
+/**
+ * A table of random keys, one for each color on each cell, which are
+ * XORed together to hash a position.
+ */
+pub struct Zobrist {
+    // the size keys are made from it as they are asked for
+    seed : u64,
+    // mixed into position keys when White is to move
+    white_key : u64,
+    keys : [[u64; 2]; CELLS],
+}
+
+// the SplitMix64 finalizer, which scrambles a counter into a random value
+const fn mix(x:u64) -> u64 {
+    let x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    let x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+const GOLDEN_GAMMA : u64 = 0x9e3779b97f4a7c15;
+
+const STANDARD_SEED : u64 = 0x636f6e6e65637436;
+
+static STANDARD : Zobrist = Zobrist::with_seed(STANDARD_SEED);
+
+impl Zobrist {
+    /**
+     * Build a table for boards up to BOARD_SIZE from the given seed. The
+     * same seed always produces the same table.
+     */
+    pub const fn with_seed(seed:u64) -> Zobrist {
+        let mut keys = [[0; 2]; CELLS];
+        let mut i = 0;
+        while i < CELLS {
+            let n = (2 * i + 1) as u64;
+            keys[i][0] = mix(seed.wrapping_add(n.wrapping_mul(GOLDEN_GAMMA)));
+            keys[i][1] = mix(seed.wrapping_add((n + 1).wrapping_mul(GOLDEN_GAMMA)));
+            i += 1;
+        }
+        // the next counter after the cells' keys
+        let white_key = mix(seed.wrapping_add((2 * CELLS as u64 + 1).wrapping_mul(GOLDEN_GAMMA)));
+        Zobrist { seed, white_key, keys }
+    }
+
+    // the key mixed into every hash of a size x size board, so that boards
+    // of different sizes with the same stones don't collide
+    const fn size_key(seed:u64, size:usize) -> u64 {
+        mix(seed ^ mix(size as u64))
+    }
+
+    // the table Board keeps its zobrist hash under
+    pub fn standard() -> &'static Zobrist {
+        &STANDARD
+    }
+
+    // the hash of the empty size x size board
+    pub fn empty_hash(&self, size:usize) -> u64 {
+        Zobrist::size_key(self.seed, size)
+    }
+
+    /**
+     * The key XORed into a hash for a stone of piece at row, col, or 0 for
+     * Empty, so that a hash can be updated as stones come and go.
+     */
+    pub fn key(&self, row:usize, col:usize, piece:Piece) -> u64 {
+        match piece {
+            Piece::Empty => 0,
+            Piece::Black => self.keys[row * BOARD_SIZE + col][0],
+            Piece::White => self.keys[row * BOARD_SIZE + col][1],
+        }
+    }
+
+    /**
+     * The key XORed into a board's hash to give the position with to_move
+     * to play: 0 for Black, so that only White's positions change.
+     */
+    pub fn side_key(&self, to_move:Piece) -> u64 {
+        assert!(to_move != Piece::Empty);
+        if to_move == Piece::White { self.white_key } else { 0 }
+    }
+
+    // hash with a stone of piece at row, col added, or removed if present
+    pub fn toggle(&self, hash:u64, row:usize, col:usize, piece:Piece) -> u64 {
+        hash ^ self.key(row, col, piece)
+    }
+
+    pub fn hash<const N: usize>(&self, board:&Board<N>) -> u64 {
+        board.stones().fold(self.empty_hash(N), |hash, (r, c, piece)| hash ^ self.key(r, c, piece))
+    }
+}
+
+/**
+ * A position's standard Zobrist hash, as a key for transposition tables
+ * which needn't hold the whole board.
+ */
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Hash)]
+pub struct BoardKey(pub u64);
+
+impl<const N: usize> Board<N> {
+    /**
+     * The board's hash under the given table, worked out from scratch.
+     * zobrist() is the same under the standard table, but kept up to date
+     * as stones are set.
+     */
+    pub fn zobrist_hash(&self, zobrist:&Zobrist) -> u64 {
+        zobrist.hash(self)
+    }
+
+    // the zobrist hash as a BoardKey
+    pub fn key(&self) -> BoardKey {
+        BoardKey(self.zobrist())
+    }
+}
+
+impl Board {
+    /**
+     * The lesser of the zobrist hashes of this board and of the board
+     * with its colors swapped, which the two boards share.
+     */
+    pub fn color_agnostic_hash(&self) -> u64 {
+        self.zobrist().min(self.flipped().zobrist())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BoardKey;
+    use super::Zobrist;
+    use super::STANDARD;
+    use super::STANDARD_SEED;
+    use board::StandardBoard as Board;
+    use board::Piece;
+    use board::BOARD_SIZE;
+    use board::CENTER;
+
+    #[test]
+    fn test_zobrist() {
+        let mut b = Board::empty();
+        let empty = b.zobrist();
+        b.set(CENTER, CENTER, Piece::Black);
+        let black = b.zobrist();
+        b.set(CENTER, CENTER, Piece::White);
+        let white = b.zobrist();
+        assert!(empty != black && black != white && empty != white);
+        b.set(CENTER, CENTER, Piece::Empty);
+        assert_eq!(b.zobrist(), empty);
+    }
+
+    #[test]
+    fn test_key() {
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        assert_eq!(b.zobrist(), STANDARD.empty_hash(BOARD_SIZE) ^ STANDARD.key(CENTER, CENTER, Piece::Black));
+        assert_eq!(STANDARD.key(CENTER, CENTER, Piece::Empty), 0);
+        assert!(STANDARD.key(0, 0, Piece::White) != STANDARD.key(0, 0, Piece::Black));
+        assert!(std::ptr::eq(Zobrist::standard(), &STANDARD));
+        assert_eq!(STANDARD.side_key(Piece::Black), 0);
+        assert!(STANDARD.side_key(Piece::White) != 0);
+    }
+
+    #[test]
+    fn test_toggle() {
+        let zobrist = Zobrist::with_seed(6);
+        let mut b = Board::empty();
+        let empty = b.zobrist_hash(&zobrist);
+        assert!(empty != Board::empty().zobrist());
+
+        let mut hash = empty;
+        for &(r, c, piece) in [(CENTER, CENTER, Piece::Black), (0, 0, Piece::White), (0, 1, Piece::White)].iter() {
+            b.set(r, c, piece);
+            hash = zobrist.toggle(hash, r, c, piece);
+            assert_eq!(hash, b.zobrist_hash(&zobrist));
+        }
+        assert_eq!(Board::empty().zobrist_hash(&STANDARD), Board::empty().zobrist());
+        assert_eq!(b.zobrist_hash(&STANDARD), b.zobrist());
+
+        // taking the stones away again
+        hash = zobrist.toggle(hash, 0, 0, Piece::White);
+        hash = zobrist.toggle(hash, 0, 1, Piece::White);
+        assert_eq!(zobrist.toggle(hash, CENTER, CENTER, Piece::Black), empty);
+    }
+
+    #[test]
+    fn test_color_agnostic_hash() {
+        let mut b = Board::empty();
+        assert_eq!(b.color_agnostic_hash(), b.zobrist());
+        b.set(CENTER, CENTER, Piece::Black);
+        b.set(0, 0, Piece::White);
+        b.set(0, 1, Piece::White);
+        let swapped = b.flipped();
+        assert!(swapped.zobrist() != b.zobrist());
+        assert_eq!(swapped.color_agnostic_hash(), b.color_agnostic_hash());
+        assert!(b.color_agnostic_hash() == b.zobrist() || b.color_agnostic_hash() == swapped.zobrist());
+
+        let mut other = b;
+        other.set(1, 1, Piece::Black);
+        assert!(other.color_agnostic_hash() != b.color_agnostic_hash());
+    }
+
+    #[test]
+    fn test_board_key() {
+        use std::collections::BTreeMap;
+        use std::collections::HashMap;
+
+        let mut a = Board::empty();
+        a.set(CENTER, CENTER, Piece::Black);
+        let mut b = Board::empty();
+        b.set(CENTER, CENTER, Piece::Black);
+        let key = a.key();
+        let copy = key;
+        assert_eq!(key, copy);
+        assert_eq!(b.key(), key);
+        assert_eq!(key, BoardKey(a.zobrist()));
+        assert!(Board::empty().key() != key);
+
+        let mut hashed = HashMap::new();
+        hashed.insert(key, 1);
+        let mut ordered = BTreeMap::new();
+        ordered.insert(key, 1);
+        assert_eq!(hashed.get(&b.key()), ordered.get(&b.key()));
+    }
+
+    #[test]
+    fn test_zobrist_includes_size() {
+        // the same stones at the same coordinates on two sizes of board
+        let mut small = super::Board::<13>::empty();
+        let mut standard = Board::empty();
+        assert!(small.zobrist() != standard.zobrist());
+        for &(r, c, piece) in [(6, 6, Piece::Black), (0, 0, Piece::White), (6, 7, Piece::Black)].iter() {
+            small.set(r, c, piece);
+            standard.set(r, c, piece);
+            assert!(small.zobrist() != standard.zobrist());
+        }
+        assert!(small.verify_hash() && standard.verify_hash());
+        assert_eq!(small.zobrist(), STANDARD.hash(&small));
+        assert!(small.zobrist() != Zobrist::with_seed(STANDARD_SEED + 1).hash(&small));
+    }
+}
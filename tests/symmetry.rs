@@ -0,0 +1,62 @@
+#[macro_use]
+extern crate proptest;
+extern crate connect6;
+
+use connect6::board::StandardBoard as Board;
+use connect6::board::Piece;
+use connect6::board::BOARD_SIZE;
+use connect6::symmetry::Symmetry;
+use proptest::collection::vec;
+
+fn board_of(stones:&[(usize, usize, bool)]) -> Board {
+    let mut board = Board::empty();
+    for &(r, c, black) in stones {
+        board.set(r, c, if black { Piece::Black } else { Piece::White });
+    }
+    board
+}
+
+// whether black and white each have six in a row somewhere
+fn wins(board:&Board) -> (bool, bool) {
+    (board.min_stones_to_win(Piece::Black) == Some(0), board.min_stones_to_win(Piece::White) == Some(0))
+}
+
+proptest! {
+    #[test]
+    fn winner_is_symmetry_invariant(stones in vec((0..BOARD_SIZE, 0..BOARD_SIZE, proptest::bool::ANY), 0..250)) {
+        let board = board_of(&stones);
+        let (black, white) = wins(&board);
+        prop_assert_eq!(board.winner().is_some(), black || white);
+        for &s in Symmetry::ALL.iter() {
+            let transformed = board.transform(s);
+            prop_assert_eq!(wins(&transformed), (black, white));
+            // when both colors have six in a row, either may be reported
+            if black != white {
+                prop_assert_eq!(transformed.winner(), board.winner());
+            }
+        }
+        prop_assert_eq!(wins(&board.rotate90()), (black, white));
+        prop_assert_eq!(wins(&board.flipped()), (white, black));
+    }
+
+    #[test]
+    fn wins_at_is_symmetry_invariant(stones in vec((0..BOARD_SIZE, 0..BOARD_SIZE, proptest::bool::ANY), 1..250)) {
+        let board = board_of(&stones);
+        let (r, c, _) = stones[stones.len() - 1];
+        let win = board.wins_at(r, c);
+        for &s in Symmetry::ALL.iter() {
+            let (tr, tc) = s.apply(r, c);
+            prop_assert_eq!(board.transform(s).wins_at(tr, tc), win);
+        }
+    }
+
+    #[test]
+    fn evaluate_is_zero_sum(stones in vec((0..BOARD_SIZE, 0..BOARD_SIZE, proptest::bool::ANY), 0..250)) {
+        let board = board_of(&stones);
+        let black = board.evaluate(Piece::Black);
+        prop_assert_eq!(black, -board.evaluate(Piece::White));
+        // swapping the colors swaps the points of view
+        prop_assert_eq!(board.flipped().evaluate(Piece::White), black);
+        prop_assert_eq!(board.flipped().evaluate(Piece::Black), -black);
+    }
+}